@@ -0,0 +1,156 @@
+//! Generates the register descriptor table (`registers::REGISTERS`) from
+//! `device.yaml`, so runtime metadata can't drift from the register map.
+
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use yaml_rust2::{Yaml, YamlLoader};
+
+const MANIFEST: &str = "device.yaml";
+
+fn main() {
+    println!("cargo:rerun-if-changed={MANIFEST}");
+
+    let source = std::fs::read_to_string(MANIFEST).expect("failed to read device.yaml");
+    let documents = YamlLoader::load_from_str(&source).expect("failed to parse device.yaml");
+    let manifest = documents
+        .first()
+        .and_then(Yaml::as_hash)
+        .expect("device.yaml must be a mapping");
+
+    let default_access = manifest
+        .get(&Yaml::String("config".into()))
+        .and_then(|c| c["default_register_access"].as_str())
+        .unwrap_or("RW");
+
+    let mut out = String::from("&[\n");
+    for (name, object) in manifest {
+        let name = name.as_str().expect("object names must be strings");
+        if name == "config" {
+            continue;
+        }
+        emit_object(&mut out, name, object, 0, default_access);
+    }
+    out.push(']');
+
+    let path = PathBuf::from(std::env::var_os("OUT_DIR").unwrap()).join("registers.rs");
+    std::fs::write(path, out).expect("failed to write registers.rs");
+}
+
+fn emit_object(out: &mut String, name: &str, object: &Yaml, base: u64, default_access: &str) {
+    match object["type"].as_str() {
+        Some("register") => {
+            for (suffix, offset) in repeats(object) {
+                let address = base + number(object, "address") + offset;
+                emit_register(
+                    out,
+                    &format!("{name}{suffix}"),
+                    address,
+                    object,
+                    default_access,
+                );
+            }
+        }
+        Some("block") => {
+            let objects = object["objects"]
+                .as_hash()
+                .expect("blocks must have objects");
+            for (suffix, offset) in repeats(object) {
+                let block_base = base + optional_number(object, "address_offset") + offset;
+                for (child, child_object) in objects {
+                    let child = child.as_str().expect("object names must be strings");
+                    let child_name = format!("{name}{suffix}.{child}");
+                    emit_object(out, &child_name, child_object, block_base, default_access);
+                }
+            }
+        }
+        // Commands, buffers and refs carry no register metadata.
+        _ => {}
+    }
+}
+
+fn emit_register(
+    out: &mut String,
+    name: &str,
+    address: u64,
+    register: &Yaml,
+    default_access: &str,
+) {
+    let access = match register["access"].as_str().unwrap_or(default_access) {
+        "RO" => "ReadOnly",
+        "WO" => "WriteOnly",
+        _ => "ReadWrite",
+    };
+    let address = u8::try_from(address).expect("register address out of range");
+
+    writeln!(out, "    RegisterInfo {{").unwrap();
+    writeln!(out, "        name: {name:?},").unwrap();
+    writeln!(out, "        address: {address:#04x},").unwrap();
+    writeln!(out, "        size_bits: {},", number(register, "size_bits")).unwrap();
+    writeln!(out, "        access: Access::{access},").unwrap();
+    writeln!(out, "        description: {:?},", description(register)).unwrap();
+    writeln!(out, "        fields: &[").unwrap();
+    if let Some(fields) = register["fields"].as_hash() {
+        for (field_name, field) in fields {
+            emit_field(out, field_name.as_str().unwrap(), field);
+        }
+    }
+    writeln!(out, "        ],").unwrap();
+    writeln!(out, "    }},").unwrap();
+}
+
+fn emit_field(out: &mut String, name: &str, field: &Yaml) {
+    writeln!(out, "            FieldInfo {{").unwrap();
+    writeln!(out, "                name: {name:?},").unwrap();
+    writeln!(out, "                start: {},", number(field, "start")).unwrap();
+    writeln!(out, "                end: {},", number(field, "end")).unwrap();
+    writeln!(
+        out,
+        "                description: {:?},",
+        description(field)
+    )
+    .unwrap();
+    write!(out, "                variants: &[").unwrap();
+    if let Some(conversion) = field["conversion"].as_hash() {
+        for (variant, value) in conversion {
+            let variant = variant.as_str().unwrap();
+            // `name` is the enum name, `default`/`catch_all` have no fixed value.
+            if let Some(value) = value.as_i64().filter(|_| variant != "name") {
+                write!(out, "({variant:?}, {value:#x}), ").unwrap();
+            }
+        }
+    }
+    writeln!(out, "],").unwrap();
+    writeln!(out, "            }},").unwrap();
+}
+
+/// Name suffixes and address offsets for each instance of a repeated object.
+fn repeats(object: &Yaml) -> Vec<(String, u64)> {
+    match &object["repeat"] {
+        Yaml::BadValue => vec![(String::new(), 0)],
+        repeat => {
+            let stride = number(repeat, "stride");
+            (0..number(repeat, "count"))
+                .map(|i| (i.to_string(), i * stride))
+                .collect()
+        }
+    }
+}
+
+fn number(object: &Yaml, key: &str) -> u64 {
+    object[key]
+        .as_i64()
+        .and_then(|n| u64::try_from(n).ok())
+        .unwrap_or_else(|| panic!("missing or invalid `{key}`"))
+}
+
+fn optional_number(object: &Yaml, key: &str) -> u64 {
+    match object[key] {
+        Yaml::BadValue => 0,
+        _ => number(object, key),
+    }
+}
+
+fn description(object: &Yaml) -> &str {
+    object["description"].as_str().unwrap_or("")
+}
@@ -0,0 +1,40 @@
+//! Bring-up and diagnostics tool for an FT6336U on Linux i2c-dev.
+//!
+//! The commands live in [`ft6336u_dd::cli`]; this only opens the device.
+
+use std::io;
+use std::process::ExitCode;
+
+use ft6336u_dd::Ft6336u;
+use ft6336u_dd::cli::{USAGE, run};
+use linux_embedded_hal::I2cdev;
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let mut device = String::from("/dev/i2c-1");
+    if args.first().map(String::as_str) == Some("-d") {
+        if args.len() < 2 {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+        device = args.remove(1);
+        args.remove(0);
+    }
+
+    let i2c = match I2cdev::new(&device) {
+        Ok(i2c) => i2c,
+        Err(e) => {
+            eprintln!("Failed to open {device}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut touch = Ft6336u::new(i2c);
+
+    match run(&mut touch, &args, &mut io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
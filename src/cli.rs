@@ -0,0 +1,222 @@
+//! Command implementations of the `ft6336u-cli` host tool.
+//!
+//! All commands run on top of a generic [`Ft6336u`], so they work against any
+//! `embedded_hal::i2c::I2c`, including a mocked bus. The binary only adds
+//! argument parsing for the i2c-dev device.
+
+use std::error::Error;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use embedded_hal::i2c::I2c;
+
+use crate::registers::{self, RegisterInfo};
+use crate::{Ft6336u, Ft6336uInterface, TouchStatus};
+
+pub const USAGE: &str = "\
+Usage: ft6336u-cli [-d <i2c-device>] <command>
+
+Commands:
+  info                         Show chip, firmware and library IDs
+  dump                         Read and decode every register
+  get <register>               Read one register (name or address)
+  set <register> <value>       Write one register
+  watch [frames]               Stream touch points until interrupted
+  rate [<active> [<monitor>]]  Show or set report rates (Hz)
+  threshold [<value>]          Show or set the touch threshold
+
+The default device is /dev/i2c-1.";
+
+pub type CliResult<T = ()> = Result<T, Box<dyn Error>>;
+pub type Driver<I> = Ft6336u<Ft6336uInterface<I>, <I as embedded_hal::i2c::ErrorType>::Error>;
+
+fn find_register(name_or_address: &str) -> CliResult<&'static RegisterInfo> {
+    let found = match parse_number(name_or_address) {
+        Ok(address) => u8::try_from(address).ok().and_then(registers::find),
+        Err(_) => registers::find_by_name(name_or_address),
+    };
+    found.ok_or_else(|| format!("unknown register '{name_or_address}'").into())
+}
+
+/// Register to write. Some addresses hold several registers (the factory
+/// registers overlay the touch report); by address, pick the writable one.
+fn find_writable_register(name_or_address: &str) -> CliResult<&'static RegisterInfo> {
+    let reg = find_register(name_or_address)?;
+    if parse_number(name_or_address).is_ok() {
+        let writable: Vec<_> = registers::REGISTERS
+            .iter()
+            .filter(|r| r.address == reg.address && r.access.is_writable())
+            .collect();
+        match writable.as_slice() {
+            [] => {}
+            [reg] => return Ok(reg),
+            regs => {
+                let names: Vec<_> = regs.iter().map(|r| r.name).collect();
+                return Err(format!(
+                    "address {:#04x} is shared by {}; use a register name",
+                    reg.address,
+                    names.join(", ")
+                )
+                .into());
+            }
+        }
+    }
+    if !reg.access.is_writable() {
+        return Err(format!("register '{}' is read-only", reg.name).into());
+    }
+    Ok(reg)
+}
+
+fn parse_number(s: &str) -> Result<u32, std::num::ParseIntError> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+fn parse_u8(s: &str) -> CliResult<u8> {
+    let value = parse_number(s).map_err(|e| format!("invalid value '{s}': {e}"))?;
+    u8::try_from(value).map_err(|_| format!("value '{s}' does not fit in 8 bits").into())
+}
+
+fn read_register<I: I2c>(touch: &mut Driver<I>, reg: &RegisterInfo) -> CliResult<u32>
+where
+    I::Error: 'static,
+{
+    let mut buf = [0u8; 4];
+    let buf = &mut buf[..reg.size_bytes()];
+    touch.read_raw(reg.address, buf)?;
+    Ok(reg.value(buf))
+}
+
+fn print_register(out: &mut impl Write, reg: &RegisterInfo, value: u32) -> io::Result<()> {
+    let width = reg.size_bytes() * 2;
+    write!(
+        out,
+        "{:#04x} {:<18} = 0x{value:0width$x}",
+        reg.address, reg.name
+    )?;
+    let bytes = value.to_be_bytes();
+    for field in reg.decode(&bytes[4 - reg.size_bytes()..]) {
+        match field.variant {
+            Some(variant) => write!(out, "  {}={variant}", field.field.name)?,
+            None => write!(out, "  {}={}", field.field.name, field.value)?,
+        }
+    }
+    writeln!(out)
+}
+
+fn info<I: I2c>(touch: &mut Driver<I>, out: &mut impl Write) -> CliResult
+where
+    I::Error: 'static,
+{
+    writeln!(out, "Chip ID:         {:#04x}", touch.read_chip_id()?)?;
+    writeln!(
+        out,
+        "Cipher:          {:#04x} {:#04x}",
+        touch.read_cipher_mid()?,
+        touch.read_cipher_low()?
+    )?;
+    writeln!(out, "FocalTech ID:    {:#04x}", touch.read_focaltech_id()?)?;
+    writeln!(out, "Firmware ID:     {:#04x}", touch.read_firmware_id()?)?;
+    writeln!(
+        out,
+        "Library version: {:#06x}",
+        touch.read_library_version()?
+    )?;
+    writeln!(
+        out,
+        "Release code ID: {:#04x}",
+        touch.read_release_code_id()?
+    )?;
+    writeln!(out, "Power mode:      {:?}", touch.read_power_mode()?)?;
+    Ok(())
+}
+
+fn dump<I: I2c>(touch: &mut Driver<I>, out: &mut impl Write) -> CliResult
+where
+    I::Error: 'static,
+{
+    for reg in registers::REGISTERS
+        .iter()
+        .filter(|r| r.access.is_readable())
+    {
+        let value = read_register(touch, reg)?;
+        print_register(out, reg, value)?;
+    }
+    Ok(())
+}
+
+fn watch<I: I2c>(touch: &mut Driver<I>, frames: Option<u64>, out: &mut impl Write) -> CliResult
+where
+    I::Error: 'static,
+{
+    let mut last = None;
+    let mut scanned = 0;
+    while frames.is_none_or(|n| scanned < n) {
+        let data = touch.scan()?;
+        scanned += 1;
+        if last != Some(data) {
+            last = Some(data);
+            for (id, point) in data.points.iter().enumerate() {
+                if point.status != TouchStatus::Release || data.touch_count == 0 {
+                    writeln!(out, "{id}: {:?} x={} y={}", point.status, point.x, point.y)?;
+                }
+            }
+            out.flush()?;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    Ok(())
+}
+
+/// Run one command line (without the program name and device option).
+pub fn run<I: I2c>(touch: &mut Driver<I>, args: &[String], out: &mut impl Write) -> CliResult
+where
+    I::Error: 'static,
+{
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["info"] => info(touch, out),
+        ["dump"] => dump(touch, out),
+        ["get", name] => {
+            let reg = find_register(name)?;
+            let value = read_register(touch, reg)?;
+            print_register(out, reg, value)?;
+            Ok(())
+        }
+        ["set", name, value] => {
+            let reg = find_writable_register(name)?;
+            let value = parse_number(value)?;
+            let bytes = value.to_be_bytes();
+            let size = reg.size_bytes();
+            if size < 4 && value >> (size * 8) != 0 {
+                return Err(format!("value does not fit in {} bits", reg.size_bits).into());
+            }
+            touch.write_raw(reg.address, &bytes[4 - size..])?;
+            print_register(out, reg, value)?;
+            Ok(())
+        }
+        ["watch"] => watch(touch, None, out),
+        ["watch", frames] => watch(touch, Some(parse_number(frames)?.into()), out),
+        ["rate", rates @ ..] if rates.len() <= 2 => {
+            if let Some(active) = rates.first() {
+                touch.write_active_rate(parse_u8(active)?)?;
+            }
+            if let Some(monitor) = rates.get(1) {
+                touch.write_monitor_rate(parse_u8(monitor)?)?;
+            }
+            writeln!(out, "Active rate:  {} Hz", touch.read_active_rate()?)?;
+            writeln!(out, "Monitor rate: {} Hz", touch.read_monitor_rate()?)?;
+            Ok(())
+        }
+        ["threshold", value @ ..] if value.len() <= 1 => {
+            if let Some(value) = value.first() {
+                touch.write_touch_threshold(parse_u8(value)?)?;
+            }
+            writeln!(out, "Threshold: {}", touch.read_touch_threshold()?)?;
+            Ok(())
+        }
+        _ => Err(USAGE.into()),
+    }
+}
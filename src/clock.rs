@@ -0,0 +1,56 @@
+//! Monotonic time sources for timestamping scans.
+//!
+//! `scan_timestamped()` stamps each result with a [`Clock`] reading and the
+//! scan sequence number, giving callers a [`crate::TouchFrame`] they can order,
+//! time gestures with and check for dropped frames.
+
+/// Monotonic microsecond counter.
+pub trait Clock {
+    fn now_us(&mut self) -> u64;
+}
+
+impl<C: Clock + ?Sized> Clock for &mut C {
+    fn now_us(&mut self) -> u64 {
+        (**self).now_us()
+    }
+}
+
+/// Clock that only moves when told to, for tests and replaying recordings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ManualClock {
+    now_us: u64,
+}
+
+impl ManualClock {
+    pub fn new(now_us: u64) -> Self {
+        Self { now_us }
+    }
+
+    pub fn set(&mut self, now_us: u64) {
+        self.now_us = now_us;
+    }
+
+    pub fn advance(&mut self, us: u64) {
+        self.now_us += us;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_us(&mut self) -> u64 {
+        self.now_us
+    }
+}
+
+/// [`Clock`] backed by `embassy_time::Instant`.
+#[cfg(feature = "embassy-time")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EmbassyClock;
+
+#[cfg(feature = "embassy-time")]
+impl Clock for EmbassyClock {
+    fn now_us(&mut self) -> u64 {
+        embassy_time::Instant::now().as_micros()
+    }
+}
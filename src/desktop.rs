@@ -0,0 +1,145 @@
+//! Virtual desktop spanning several touch panels.
+//!
+//! Each panel's contacts are mapped into a shared coordinate space by a
+//! [`PanelTransform`] (mounting rotation and mirroring, then an offset), and
+//! get a desktop-wide ID of `panel * 2 + slot`, so IDs stay unique across
+//! panels. `Panels` does this for drivers of the same type; with mixed bus
+//! types, scan each driver yourself and use [`DesktopFrame::from_panels`].
+
+use thiserror::Error;
+
+use crate::{Ft6336uError, TouchData, TouchPoint};
+
+/// Clockwise rotation of a panel as mounted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub enum Rotation {
+    #[default]
+    None,
+    Cw90,
+    Cw180,
+    Cw270,
+}
+
+/// Maps panel coordinates into the desktop: mirror, then rotate within the
+/// panel's `width` x `height`, then move by the offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct PanelTransform {
+    /// Panel size as reported, before rotation.
+    pub width: u16,
+    pub height: u16,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub rotation: Rotation,
+    /// Desktop position of the panel's (rotated) top-left corner.
+    pub offset_x: u16,
+    pub offset_y: u16,
+}
+
+impl PanelTransform {
+    /// A `width` x `height` panel at the desktop origin, unrotated.
+    pub const fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            flip_x: false,
+            flip_y: false,
+            rotation: Rotation::None,
+            offset_x: 0,
+            offset_y: 0,
+        }
+    }
+
+    pub fn apply(&self, x: u16, y: u16) -> (u16, u16) {
+        let max_x = self.width.saturating_sub(1);
+        let max_y = self.height.saturating_sub(1);
+        let x = x.min(max_x);
+        let y = y.min(max_y);
+        let x = if self.flip_x { max_x - x } else { x };
+        let y = if self.flip_y { max_y - y } else { y };
+        let (x, y) = match self.rotation {
+            Rotation::None => (x, y),
+            Rotation::Cw90 => (max_y - y, x),
+            Rotation::Cw180 => (max_x - x, max_y - y),
+            Rotation::Cw270 => (y, max_x - x),
+        };
+        (
+            x.saturating_add(self.offset_x),
+            y.saturating_add(self.offset_y),
+        )
+    }
+
+    /// Map both slots of `panel`'s report into the desktop.
+    pub fn map(&self, panel: usize, data: &TouchData) -> [DesktopPoint; 2] {
+        core::array::from_fn(|slot| {
+            let mut point = data.points[slot];
+            (point.x, point.y) = self.apply(point.x, point.y);
+            DesktopPoint {
+                id: panel * 2 + slot,
+                panel,
+                point,
+            }
+        })
+    }
+}
+
+/// A contact in desktop coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct DesktopPoint {
+    /// Desktop-wide ID, `panel * 2 + slot`.
+    pub id: usize,
+    pub panel: usize,
+    /// The panel's report with `x`/`y` transformed.
+    pub point: TouchPoint,
+}
+
+/// One scan of every panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DesktopFrame<const N: usize> {
+    pub points: [[DesktopPoint; 2]; N],
+}
+
+impl<const N: usize> DesktopFrame<N> {
+    pub fn from_panels(panels: &[TouchData; N], transforms: &[PanelTransform; N]) -> Self {
+        Self {
+            points: core::array::from_fn(|panel| transforms[panel].map(panel, &panels[panel])),
+        }
+    }
+
+    /// All slots of all panels, in ID order.
+    pub fn iter(&self) -> impl Iterator<Item = &DesktopPoint> {
+        self.points.iter().flatten()
+    }
+
+    /// Contact by desktop-wide ID.
+    pub fn get(&self, id: usize) -> Option<&DesktopPoint> {
+        self.points.get(id / 2).map(|slots| &slots[id % 2])
+    }
+}
+
+/// An error from one panel of a `Panels` set.
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[error("Panel {panel}: {error}")]
+pub struct PanelError<I2cErr> {
+    pub panel: usize,
+    pub error: Ft6336uError<I2cErr>,
+}
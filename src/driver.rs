@@ -1,8 +1,15 @@
 use super::{I2c, RegisterInterface, bisync, only_async, only_sync};
+use crate::clock::Clock;
+use crate::noise::NoiseMonitor;
+use crate::quirks::{self, FirmwareIdentity, KNOWN_QUIRKS, QuirkEntry, Quirks};
+use crate::rate::RateMonitor;
+use crate::tuning::ThresholdTuner;
 use crate::{
-    CtrlMode, DeviceMode, FT6336U_I2C_ADDRESS, Ft6336uError, Ft6336uInterface, Ft6336uLowLevel,
-    PowerModeEnum, TouchData, TouchEvent, TouchStatus,
+    CtrlMode, DeviceMode, FT6336U_I2C_ADDRESS, FactoryDataType, Ft6336uError, Ft6336uInterface,
+    Ft6336uLowLevel, Ft6336uState, OperatingState, PowerModeEnum, ProximityState, RawFrame,
+    TouchData, TouchEvent, TouchFrame,
 };
+use core::borrow::{Borrow, BorrowMut};
 
 #[bisync]
 impl<I2CBus, E> RegisterInterface for Ft6336uInterface<I2CBus>
@@ -49,9 +56,10 @@ where
 pub struct Ft6336u<
     I2CImpl: RegisterInterface<AddressType = u8, Error = Ft6336uError<I2CBusErr>>,
     I2CBusErr: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState> = Ft6336uState,
 > {
     pub ll: Ft6336uLowLevel<I2CImpl>,
-    touch_data: TouchData,
+    state: State,
     _marker: core::marker::PhantomData<I2CBusErr>,
 }
 
@@ -61,12 +69,39 @@ where
     E: core::fmt::Debug,
 {
     pub fn new(i2c: I2CBus) -> Self {
+        Self::with_state(i2c, Ft6336uState::new())
+    }
+}
+
+impl<I2CBus, E, State> Ft6336u<Ft6336uInterface<I2CBus>, E, State>
+where
+    I2CBus: I2c<Error = E>,
+    E: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState>,
+{
+    pub fn with_state(i2c: I2CBus, state: State) -> Self {
         Self {
             ll: Ft6336uLowLevel::new(Ft6336uInterface::new(i2c)),
-            touch_data: TouchData::default(),
+            state,
             _marker: core::marker::PhantomData,
         }
     }
+
+    /// Direct access to the I2C bus, e.g. for talking to the bootloader.
+    pub fn i2c_mut(&mut self) -> &mut I2CBus {
+        self.ll.interface().i2c_mut()
+    }
+
+    /// Tear the driver down and return the I2C bus.
+    pub fn release(self) -> I2CBus {
+        self.into_parts().0
+    }
+
+    /// Tear the driver down and return the I2C bus and the tracking state.
+    pub fn into_parts(self) -> (I2CBus, State) {
+        let Self { ll, state, .. } = self;
+        (ll.interface.into_inner(), state)
+    }
 }
 
 pub trait CurrentFt6336uDriverInterface<E>:
@@ -83,10 +118,11 @@ where
 
 include!("bisync_helpers.rs");
 
-impl<I2CImpl, I2CBusErr> Ft6336u<I2CImpl, I2CBusErr>
+impl<I2CImpl, I2CBusErr, State> Ft6336u<I2CImpl, I2CBusErr, State>
 where
     I2CImpl: CurrentFt6336uDriverInterface<I2CBusErr>,
     I2CBusErr: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState>,
 {
     // === Device Mode (0x00) ===
 
@@ -106,6 +142,66 @@ where
         write_internal(&mut op, |r| r.set_mode(mode)).await
     }
 
+    // === Factory Mode Registers (valid only while DeviceMode is Factory) ===
+
+    #[bisync]
+    pub async fn start_factory_scan(&mut self) -> Result<(), Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.device_mode();
+        modify_internal(&mut op, |r| r.set_start_scan(true)).await
+    }
+
+    #[bisync]
+    pub async fn read_factory_scan_busy(&mut self) -> Result<bool, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.device_mode();
+        let reg = read_internal(&mut op).await?;
+        Ok(reg.start_scan())
+    }
+
+    #[bisync]
+    pub async fn read_factory_line_num(&mut self) -> Result<u8, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.factory_line_num();
+        let reg = read_internal(&mut op).await?;
+        Ok(reg.value())
+    }
+
+    #[bisync]
+    pub async fn write_factory_line_num(&mut self, val: u8) -> Result<(), Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.factory_line_num();
+        write_internal(&mut op, |r| r.set_value(val)).await
+    }
+
+    #[bisync]
+    pub async fn read_factory_tx_num(&mut self) -> Result<u8, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.factory_tx_num();
+        let reg = read_internal(&mut op).await?;
+        Ok(reg.value())
+    }
+
+    #[bisync]
+    pub async fn read_factory_rx_num(&mut self) -> Result<u8, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.factory_rx_num();
+        let reg = read_internal(&mut op).await?;
+        Ok(reg.value())
+    }
+
+    #[bisync]
+    pub async fn read_factory_data_type(
+        &mut self,
+    ) -> Result<FactoryDataType, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.factory_data_type();
+        let reg = read_internal(&mut op).await?;
+        Ok(reg.data_type())
+    }
+
+    #[bisync]
+    pub async fn write_factory_data_type(
+        &mut self,
+        val: FactoryDataType,
+    ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.factory_data_type();
+        write_internal(&mut op, |r| r.set_data_type(val)).await
+    }
+
     // === Touch Detection Status (0x02) ===
 
     #[bisync]
@@ -117,20 +213,22 @@ where
 
     // === Touch Point Data (0x03-0x0E, block repeated for 2 points) ===
 
+    /// X coordinate of `point`, with the firmware quirks applied like `scan()`.
     #[bisync]
     pub async fn read_touch_x(&mut self, point: usize) -> Result<u16, Ft6336uError<I2CBusErr>> {
         let mut block = self.ll.tp(point);
         let mut op = block.xevent();
         let reg = read_internal(&mut op).await?;
-        Ok(reg.x())
+        Ok(self.state.borrow().quirks().correct_x(reg.x()))
     }
 
+    /// Y coordinate of `point`, with the firmware quirks applied like `scan()`.
     #[bisync]
     pub async fn read_touch_y(&mut self, point: usize) -> Result<u16, Ft6336uError<I2CBusErr>> {
         let mut block = self.ll.tp(point);
         let mut op = block.yid();
         let reg = read_internal(&mut op).await?;
-        Ok(reg.y())
+        Ok(self.state.borrow().quirks().correct_y(reg.y()))
     }
 
     #[bisync]
@@ -179,10 +277,69 @@ where
 
     #[bisync]
     pub async fn write_touch_threshold(&mut self, val: u8) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0x80)?;
         let mut op = self.ll.threshold();
         write_internal(&mut op, |r| r.set_value(val)).await
     }
 
+    // === Threshold Group (0x81-0x84) ===
+
+    #[bisync]
+    pub async fn read_peak_threshold(&mut self) -> Result<u8, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.th_peak();
+        let reg = read_internal(&mut op).await?;
+        Ok(reg.value())
+    }
+
+    #[bisync]
+    pub async fn write_peak_threshold(&mut self, val: u8) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0x81)?;
+        let mut op = self.ll.th_peak();
+        write_internal(&mut op, |r| r.set_value(val)).await
+    }
+
+    #[bisync]
+    pub async fn read_cal_threshold(&mut self) -> Result<u8, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.th_cal();
+        let reg = read_internal(&mut op).await?;
+        Ok(reg.value())
+    }
+
+    #[bisync]
+    pub async fn write_cal_threshold(&mut self, val: u8) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0x82)?;
+        let mut op = self.ll.th_cal();
+        write_internal(&mut op, |r| r.set_value(val)).await
+    }
+
+    #[bisync]
+    pub async fn read_water_threshold(&mut self) -> Result<u8, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.th_water();
+        let reg = read_internal(&mut op).await?;
+        Ok(reg.value())
+    }
+
+    #[bisync]
+    pub async fn write_water_threshold(&mut self, val: u8) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0x83)?;
+        let mut op = self.ll.th_water();
+        write_internal(&mut op, |r| r.set_value(val)).await
+    }
+
+    #[bisync]
+    pub async fn read_temp_threshold(&mut self) -> Result<u8, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.th_temp();
+        let reg = read_internal(&mut op).await?;
+        Ok(reg.value())
+    }
+
+    #[bisync]
+    pub async fn write_temp_threshold(&mut self, val: u8) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0x84)?;
+        let mut op = self.ll.th_temp();
+        write_internal(&mut op, |r| r.set_value(val)).await
+    }
+
     // === Filter Coefficient (0x85) ===
 
     #[bisync]
@@ -197,6 +354,7 @@ where
         &mut self,
         val: u8,
     ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0x85)?;
         let mut op = self.ll.filter_coefficient();
         write_internal(&mut op, |r| r.set_value(val)).await
     }
@@ -212,6 +370,7 @@ where
 
     #[bisync]
     pub async fn write_ctrl_mode(&mut self, mode: CtrlMode) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0x86)?;
         let mut op = self.ll.ctrl();
         write_internal(&mut op, |r| r.set_mode(mode)).await
     }
@@ -230,6 +389,7 @@ where
         &mut self,
         val: u8,
     ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0x87)?;
         let mut op = self.ll.time_enter_monitor();
         write_internal(&mut op, |r| r.set_value(val)).await
     }
@@ -245,6 +405,7 @@ where
 
     #[bisync]
     pub async fn write_active_rate(&mut self, val: u8) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0x88)?;
         let mut op = self.ll.active_mode_rate();
         write_internal(&mut op, |r| r.set_value(val)).await
     }
@@ -260,6 +421,7 @@ where
 
     #[bisync]
     pub async fn write_monitor_rate(&mut self, val: u8) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0x89)?;
         let mut op = self.ll.monitor_mode_rate();
         write_internal(&mut op, |r| r.set_value(val)).await
     }
@@ -275,12 +437,50 @@ where
 
     #[bisync]
     pub async fn write_freq_hopping_en(&mut self, val: u8) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0x8B)?;
         let mut op = self.ll.freq_hopping_en();
         write_internal(&mut op, |r| r.set_value(val)).await
     }
 
+    /// Enable frequency hopping, which suppresses the phantom touches caused
+    /// by charger noise.
+    #[bisync]
+    pub async fn set_charger_mode(&mut self, enable: bool) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.write_freq_hopping_en(u8::from(enable)).await
+    }
+
     // === System Information (0x9F-0xBC) ===
 
+    /// Read the chip and firmware identity and apply the matching entry of
+    /// [`KNOWN_QUIRKS`]. Call once after power-up or reset.
+    #[bisync]
+    pub async fn init(&mut self) -> Result<FirmwareIdentity, Ft6336uError<I2CBusErr>> {
+        self.init_with_quirks(KNOWN_QUIRKS).await
+    }
+
+    /// Like [`Self::init`], but with a caller-provided quirks table.
+    #[bisync]
+    pub async fn init_with_quirks(
+        &mut self,
+        table: &[QuirkEntry],
+    ) -> Result<FirmwareIdentity, Ft6336uError<I2CBusErr>> {
+        let id = FirmwareIdentity {
+            chip_id: self.read_chip_id().await?,
+            firmware_id: self.read_firmware_id().await?,
+            library_version: self.read_library_version().await?,
+            release_code_id: self.read_release_code_id().await?,
+        };
+        let quirks = quirks::lookup(table, &id);
+        if quirks != Quirks::NONE {
+            info!("Applying firmware quirks for {:?}", id);
+        }
+        self.state.borrow_mut().set_quirks(quirks);
+        match quirks.unusable {
+            Some(reason) => Err(Ft6336uError::NotSupported(reason)),
+            None => Ok(id),
+        }
+    }
+
     #[bisync]
     pub async fn read_cipher_mid(&mut self) -> Result<u8, Ft6336uError<I2CBusErr>> {
         let mut op = self.ll.cipher_mid();
@@ -321,6 +521,7 @@ where
         &mut self,
         mode: PowerModeEnum,
     ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0xA5)?;
         let mut op = self.ll.power_mode();
         write_internal(&mut op, |r| r.set_mode(mode)).await
     }
@@ -332,6 +533,39 @@ where
         Ok(reg.value())
     }
 
+    #[bisync]
+    pub async fn read_operating_state(
+        &mut self,
+    ) -> Result<OperatingState, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.operating_state();
+        let reg = read_internal(&mut op).await?;
+        Ok(reg.state())
+    }
+
+    #[bisync]
+    pub async fn read_error_code(&mut self) -> Result<u8, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.error_code();
+        let reg = read_internal(&mut op).await?;
+        Ok(reg.value())
+    }
+
+    #[bisync]
+    pub async fn read_big_area_threshold(&mut self) -> Result<u8, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.big_area_threshold();
+        let reg = read_internal(&mut op).await?;
+        Ok(reg.value())
+    }
+
+    #[bisync]
+    pub async fn write_big_area_threshold(
+        &mut self,
+        val: u8,
+    ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0xAE)?;
+        let mut op = self.ll.big_area_threshold();
+        write_internal(&mut op, |r| r.set_value(val)).await
+    }
+
     #[bisync]
     pub async fn read_focaltech_id(&mut self) -> Result<u8, Ft6336uError<I2CBusErr>> {
         let mut op = self.ll.focaltech_id();
@@ -355,10 +589,44 @@ where
 
     #[bisync]
     pub async fn write_face_dec_mode(&mut self, val: u8) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(0xB0)?;
         let mut op = self.ll.face_dec_mode();
         write_internal(&mut op, |r| r.set_value(val)).await
     }
 
+    /// Turn proximity (face) detection on or off. While on, `scan()` reports
+    /// the result in [`TouchData::proximity`].
+    ///
+    /// Decoding is switched on before the register write and off only after
+    /// it, so it stays on whenever the chip may be reporting, even if the
+    /// call fails or is cancelled. Whether the write itself reached the chip
+    /// is then unknown; call again to be sure.
+    #[bisync]
+    pub async fn enable_proximity(&mut self, enable: bool) -> Result<(), Ft6336uError<I2CBusErr>> {
+        // Decoding with the chip not reporting is harmless: the gesture ID
+        // then never holds a proximity result.
+        if enable {
+            self.state.borrow_mut().set_proximity_enabled(true);
+        }
+        self.write_face_dec_mode(u8::from(enable)).await?;
+        if !enable {
+            self.state.borrow_mut().set_proximity_enabled(false);
+        }
+        Ok(())
+    }
+
+    /// Current proximity result, or `None` while the gesture ID holds no
+    /// result, e.g. between proximity changes or with proximity detection
+    /// disabled.
+    #[bisync]
+    pub async fn read_proximity(
+        &mut self,
+    ) -> Result<Option<ProximityState>, Ft6336uError<I2CBusErr>> {
+        let mut op = self.ll.gesture_id();
+        let reg = read_internal(&mut op).await?;
+        Ok(ProximityState::from_gesture(reg.gesture()))
+    }
+
     #[bisync]
     pub async fn read_state(&mut self) -> Result<u8, Ft6336uError<I2CBusErr>> {
         let mut op = self.ll.state();
@@ -372,55 +640,152 @@ where
         write_internal(&mut op, |r| r.set_value(val)).await
     }
 
-    // === Scan (reads all touch points in a single I2C transaction) ===
+    // === Raw register access (see `registers` for the register map) ===
 
     #[bisync]
-    pub async fn scan(&mut self) -> Result<TouchData, Ft6336uError<I2CBusErr>> {
-        // Batch read registers 0x02-0x0E (13 bytes) in one I2C transaction:
-        // buf[0]:    TdStatus (touch count in bits 3:0)
-        // buf[1..7]: Touch point 0: XEvent(2B) + YId(2B) + Weight(1B) + Misc(1B)
-        // buf[7..13]: Touch point 1: XEvent(2B) + YId(2B) + Weight(1B) + Misc(1B)
-        //
-        // XEvent (BE 16-bit): event = bits 15:14 (high[7:6]), x = bits 11:0 (high[3:0] << 8 | low)
-        // YId    (BE 16-bit): id    = bits 15:12 (high[7:4]), y = bits 11:0 (high[3:0] << 8 | low)
-        let mut buf = [0u8; 13];
-        self.ll.interface().read_register(0x02, 0, &mut buf).await?;
-
-        let touch_count = buf[0] & 0x0F;
-        self.touch_data.touch_count = touch_count;
-
-        if touch_count == 0 {
-            self.touch_data.points[0].status = TouchStatus::Release;
-            self.touch_data.points[1].status = TouchStatus::Release;
+    pub async fn read_raw(
+        &mut self,
+        address: u8,
+        data: &mut [u8],
+    ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.ll.interface().read_register(address, 0, data).await
+    }
+
+    #[bisync]
+    pub async fn write_raw(
+        &mut self,
+        address: u8,
+        data: &[u8],
+    ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.check_supported(address)?;
+        self.ll.interface().write_register(address, 0, data).await
+    }
+
+    fn check_supported(&self, address: u8) -> Result<(), Ft6336uError<I2CBusErr>> {
+        if self.state.borrow().quirks().is_supported(address) {
+            Ok(())
         } else {
-            let count = core::cmp::min(touch_count as usize, 2);
-            let mut seen = [false; 2];
-
-            for i in 0..count {
-                let off = 1 + i * 6;
-                let id = ((buf[off + 2] >> 4) & 0x0F) as usize;
-                if id < 2 {
-                    seen[id] = true;
-                    let x = (((buf[off] & 0x0F) as u16) << 8) | (buf[off + 1] as u16);
-                    let y = (((buf[off + 2] & 0x0F) as u16) << 8) | (buf[off + 3] as u16);
-
-                    let prev_status = self.touch_data.points[id].status;
-                    self.touch_data.points[id].status = match prev_status {
-                        TouchStatus::Release => TouchStatus::Touch,
-                        _ => TouchStatus::Stream,
-                    };
-                    self.touch_data.points[id].x = x;
-                    self.touch_data.points[id].y = y;
-                }
-            }
-
-            for (id, &was_seen) in seen.iter().enumerate() {
-                if !was_seen {
-                    self.touch_data.points[id].status = TouchStatus::Release;
-                }
-            }
+            Err(Ft6336uError::NotSupported(
+                "Register is ignored by this firmware",
+            ))
         }
+    }
+
+    // === Scan (reads gesture ID and all touch points in a single I2C transaction) ===
+
+    #[bisync]
+    pub async fn read_raw_frame(&mut self) -> Result<RawFrame, Ft6336uError<I2CBusErr>> {
+        let mut frame = RawFrame::default();
+        self.ll
+            .interface()
+            .read_register(0x01, 0, &mut frame.bytes)
+            .await?;
+        frame.sequence = self.state.borrow_mut().next_sequence();
+        Ok(frame)
+    }
 
-        Ok(self.touch_data)
+    /// Read and decode one frame.
+    ///
+    /// Cancel-safe: the tracking state is only updated after the burst read
+    /// completes, so dropping the future leaves it as it was. The same holds
+    /// for all `scan_*` variants.
+    #[bisync]
+    pub async fn scan(&mut self) -> Result<TouchData, Ft6336uError<I2CBusErr>> {
+        let frame = self.read_raw_frame().await?;
+        Ok(self.state.borrow_mut().update(&frame))
+    }
+
+    /// Decode a frame from `read_raw_frame()` into the tracking state.
+    pub(crate) fn decode(&mut self, frame: &RawFrame) -> TouchData {
+        self.state.borrow_mut().update(frame)
+    }
+
+    /// `scan()`, stamped with `clock` and the frame's sequence number.
+    #[bisync]
+    pub async fn scan_timestamped(
+        &mut self,
+        clock: &mut impl Clock,
+    ) -> Result<TouchFrame, Ft6336uError<I2CBusErr>> {
+        let frame = self.read_raw_frame().await?;
+        let timestamp_us = clock.now_us();
+        Ok(TouchFrame {
+            data: self.state.borrow_mut().update(&frame),
+            timestamp_us,
+            sequence: frame.sequence,
+        })
+    }
+
+    /// [`Self::scan_timestamped`], also feeding the burst to `monitor` for
+    /// freshness and report-rate tracking.
+    #[bisync]
+    pub async fn scan_with_rate_monitor(
+        &mut self,
+        monitor: &mut RateMonitor,
+        clock: &mut impl Clock,
+    ) -> Result<TouchFrame, Ft6336uError<I2CBusErr>> {
+        let frame = self.read_raw_frame().await?;
+        let timestamp_us = clock.now_us();
+        monitor.observe(&frame, timestamp_us);
+        Ok(TouchFrame {
+            data: self.state.borrow_mut().update(&frame),
+            timestamp_us,
+            sequence: frame.sequence,
+        })
+    }
+
+    /// `scan()`, feeding the result to `tuner` and writing the touch threshold
+    /// whenever it decides on a new one. If the firmware ignores `Threshold`
+    /// (see [`Quirks::unsupported_registers`]), the write is skipped and only
+    /// the scan result is returned.
+    #[bisync]
+    pub async fn scan_with_tuner(
+        &mut self,
+        tuner: &mut ThresholdTuner,
+    ) -> Result<TouchData, Ft6336uError<I2CBusErr>> {
+        let frame = self.read_raw_frame().await?;
+        let mut state = *self.state.borrow();
+        let data = state.update(&frame);
+        let mut next = *tuner;
+        let threshold = next.observe(&data);
+        if let Some(threshold) = threshold.filter(|_| state.quirks().is_supported(0x80)) {
+            self.write_touch_threshold(threshold).await?;
+        }
+        // Commit after the last await, so a cancelled or failed call leaves
+        // both untouched and the next one retries the write.
+        *self.state.borrow_mut() = state;
+        *tuner = next;
+        Ok(data)
+    }
+
+    /// `scan()`, feeding the result to `monitor`. When the monitor detects
+    /// charger noise, frequency hopping is enabled and `on_switch` is called.
+    /// If the firmware ignores `FreqHoppingEn` (see
+    /// [`Quirks::unsupported_registers`]), the detection is only logged.
+    #[bisync]
+    pub async fn scan_with_noise_monitor(
+        &mut self,
+        monitor: &mut NoiseMonitor,
+        on_switch: impl FnOnce(),
+    ) -> Result<TouchData, Ft6336uError<I2CBusErr>> {
+        let frame = self.read_raw_frame().await?;
+        let mut state = *self.state.borrow();
+        let data = state.update(&frame);
+        let mut next = *monitor;
+        let mut switch = next.observe(&data);
+        if switch && !state.quirks().is_supported(0x8B) {
+            warn!("Charger noise detected, but frequency hopping is not supported");
+            switch = false;
+        }
+        if switch {
+            info!("Charger noise detected, enabling frequency hopping");
+            self.set_charger_mode(true).await?;
+        }
+        // See `scan_with_tuner`.
+        *self.state.borrow_mut() = state;
+        *monitor = next;
+        if switch {
+            on_switch();
+        }
+        Ok(data)
     }
 }
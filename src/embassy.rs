@@ -0,0 +1,129 @@
+//! Ready-made Embassy touch task.
+//!
+//! [`run_touch_task`] waits for the INT pin, scans the controller and pushes
+//! every changed [`TouchData`] frame into an `embassy_sync` channel or
+//! `PubSubChannel`. Embassy tasks cannot be generic, so wrap it in a concrete
+//! task in the application:
+//!
+//! ```rust,ignore
+//! static TOUCH: Channel<CriticalSectionRawMutex, TouchData, 4> = Channel::new();
+//!
+//! #[embassy_executor::task]
+//! async fn touch_task(mut touch: Ft6336uAsync<Ft6336uInterface<I2c<'static, Async>>, I2cError>, mut int: Input<'static>) {
+//!     let mut sender = TOUCH.sender();
+//!     let err = run_touch_task(&mut touch, &mut int, &mut sender, Overflow::Wait).await;
+//!     error!("touch task stopped: {:?}", err);
+//! }
+//! ```
+
+use core::borrow::BorrowMut;
+use core::convert::Infallible;
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::channel::Sender;
+use embassy_sync::pubsub::{ImmediatePublisher, Publisher};
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::I2c;
+use thiserror::Error;
+
+use crate::{Ft6336uAsync, Ft6336uError, Ft6336uInterface, Ft6336uState, TouchData};
+
+/// What to do with a frame when the receiving side is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Overflow {
+    /// Wait until the receiver makes room. Scanning pauses meanwhile.
+    #[default]
+    Wait,
+    /// Never wait. A `Channel` drops the new frame, a `PubSubChannel` drops
+    /// the oldest queued frame instead.
+    Drop,
+}
+
+/// Destination for frames produced by [`run_touch_task`].
+pub trait TouchSink {
+    /// Queue `data`. Returns whether it was accepted, `false` if it was
+    /// dropped because the receiving side is full.
+    fn push(&mut self, data: TouchData, overflow: Overflow) -> impl Future<Output = bool>;
+}
+
+impl<M: RawMutex, const N: usize> TouchSink for Sender<'_, M, TouchData, N> {
+    async fn push(&mut self, data: TouchData, overflow: Overflow) -> bool {
+        match overflow {
+            Overflow::Wait => {
+                self.send(data).await;
+                true
+            }
+            Overflow::Drop => {
+                let accepted = self.try_send(data).is_ok();
+                if !accepted {
+                    warn!("Touch channel full, frame dropped");
+                }
+                accepted
+            }
+        }
+    }
+}
+
+impl<M: RawMutex, const CAP: usize, const SUBS: usize, const PUBS: usize> TouchSink
+    for Publisher<'_, M, TouchData, CAP, SUBS, PUBS>
+{
+    async fn push(&mut self, data: TouchData, overflow: Overflow) -> bool {
+        // Immediate publishing makes room by dropping the oldest frame.
+        match overflow {
+            Overflow::Wait => self.publish(data).await,
+            Overflow::Drop => self.publish_immediate(data),
+        }
+        true
+    }
+}
+
+impl<M: RawMutex, const CAP: usize, const SUBS: usize, const PUBS: usize> TouchSink
+    for ImmediatePublisher<'_, M, TouchData, CAP, SUBS, PUBS>
+{
+    async fn push(&mut self, data: TouchData, _overflow: Overflow) -> bool {
+        self.publish_immediate(data);
+        true
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TouchTaskError<I2cErr, PinErr> {
+    #[error("Touch controller error: {0}")]
+    Touch(Ft6336uError<I2cErr>),
+    #[error("INT pin error")]
+    Int(PinErr),
+}
+
+/// Wait on INT, scan and push changed frames into `sink`, forever.
+///
+/// The controller should be in [`GestureMode::Trigger`](crate::GestureMode::Trigger)
+/// so INT pulses once per report. Frames identical to the last accepted one
+/// are skipped, so a finger resting still does not flood the receiver. A
+/// frame the sink dropped is not remembered, so the next scan sends it again
+/// if it is still current. Only returns on an I2C or INT pin error.
+pub async fn run_touch_task<I2CBus, E, State, Int, Sink>(
+    touch: &mut Ft6336uAsync<Ft6336uInterface<I2CBus>, E, State>,
+    int: &mut Int,
+    sink: &mut Sink,
+    overflow: Overflow,
+) -> Result<Infallible, TouchTaskError<E, Int::Error>>
+where
+    I2CBus: I2c<Error = E>,
+    E: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState>,
+    Int: Wait,
+    Sink: TouchSink,
+{
+    let mut last = TouchData::default();
+    loop {
+        int.wait_for_falling_edge()
+            .await
+            .map_err(TouchTaskError::Int)?;
+        let data = touch.scan().await.map_err(TouchTaskError::Touch)?;
+        if data != last && sink.push(data, overflow).await {
+            last = data;
+        }
+    }
+}
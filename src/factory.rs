@@ -0,0 +1,185 @@
+use super::{CurrentFt6336uDriverInterface, DelayNs, Ft6336u, RegisterInterface};
+use super::{bisync, only_sync};
+use crate::{DeviceMode, FACTORY_MAX_CHANNELS, FactoryDataType, Ft6336uError, Ft6336uState};
+use core::borrow::BorrowMut;
+
+const FACTORY_RAW_DATA: u8 = 0x10;
+const MODE_SWITCH_DELAY_MS: u32 = 300;
+const SCAN_POLL_INTERVAL_MS: u32 = 10;
+const SCAN_POLL_ATTEMPTS: u32 = 100;
+
+/// The controller switched into factory mode, giving access to per-channel
+/// sensor data.
+///
+/// Created by `enter_factory_mode()`. Touch reporting is suspended until
+/// `exit()` restores [`DeviceMode::Working`]. The blocking guard also restores
+/// it on drop; the async guard cannot, so always call `exit().await`.
+///
+/// The async factory operations are not cancel-safe. A cancelled
+/// `enter_factory_mode()` or read may leave the chip in factory mode, possibly
+/// mid-scan; restore it with `write_device_mode(DeviceMode::Working)`.
+pub struct FactoryMode<'a, I2CImpl, I2CBusErr, State>
+where
+    I2CImpl: RegisterInterface<AddressType = u8, Error = Ft6336uError<I2CBusErr>>,
+    I2CBusErr: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState>,
+{
+    touch: &'a mut Ft6336u<I2CImpl, I2CBusErr, State>,
+    // Only read by the blocking `Drop` impl.
+    #[allow(dead_code)]
+    active: bool,
+}
+
+impl<I2CImpl, I2CBusErr, State> Ft6336u<I2CImpl, I2CBusErr, State>
+where
+    I2CImpl: CurrentFt6336uDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState>,
+{
+    #[bisync]
+    pub async fn enter_factory_mode(
+        &mut self,
+        delay: &mut impl DelayNs,
+    ) -> Result<FactoryMode<'_, I2CImpl, I2CBusErr, State>, Ft6336uError<I2CBusErr>> {
+        self.write_device_mode(DeviceMode::Factory).await?;
+        delay.delay_ms(MODE_SWITCH_DELAY_MS).await;
+        Ok(FactoryMode {
+            touch: self,
+            active: true,
+        })
+    }
+}
+
+impl<I2CImpl, I2CBusErr, State> FactoryMode<'_, I2CImpl, I2CBusErr, State>
+where
+    I2CImpl: CurrentFt6336uDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState>,
+{
+    /// The underlying driver, e.g. for the `*_factory_*` register accessors.
+    pub fn driver(&mut self) -> &mut Ft6336u<I2CImpl, I2CBusErr, State> {
+        self.touch
+    }
+
+    /// Number of sensor channels. On this self-capacitance controller every
+    /// TX and RX electrode is sampled as its own channel.
+    #[bisync]
+    pub async fn channel_count(&mut self) -> Result<usize, Ft6336uError<I2CBusErr>> {
+        let tx = self.touch.read_factory_tx_num().await?;
+        let rx = self.touch.read_factory_rx_num().await?;
+        Ok(tx as usize + rx as usize)
+    }
+
+    /// Trigger a new sensor scan and wait for the chip to finish it.
+    #[bisync]
+    pub async fn rescan(
+        &mut self,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.touch.start_factory_scan().await?;
+        for _ in 0..SCAN_POLL_ATTEMPTS {
+            delay.delay_ms(SCAN_POLL_INTERVAL_MS).await;
+            if !self.touch.read_factory_scan_busy().await? {
+                return Ok(());
+            }
+        }
+        Err(Ft6336uError::Timeout("Factory scan did not complete"))
+    }
+
+    /// Scan and read one `u16` per channel of the given data type into `out`.
+    #[bisync]
+    pub async fn read_channels(
+        &mut self,
+        data_type: FactoryDataType,
+        delay: &mut impl DelayNs,
+        out: &mut [u16],
+    ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        check_channel_count(out.len())?;
+        self.touch.write_factory_data_type(data_type).await?;
+        self.rescan(delay).await?;
+        self.touch.write_factory_line_num(0).await?;
+
+        let mut buf = [0u8; 2 * FACTORY_MAX_CHANNELS];
+        let bytes = &mut buf[..2 * out.len()];
+        self.touch.read_raw(FACTORY_RAW_DATA, bytes).await?;
+        for (value, chunk) in out.iter_mut().zip(bytes.chunks_exact(2)) {
+            *value = u16::from_be_bytes([chunk[0], chunk[1]]);
+        }
+        Ok(())
+    }
+
+    /// Raw capacitance counts per channel.
+    #[bisync]
+    pub async fn read_raw_data(
+        &mut self,
+        delay: &mut impl DelayNs,
+        out: &mut [u16],
+    ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.read_channels(FactoryDataType::Raw, delay, out).await
+    }
+
+    /// Difference between raw data and the baseline per channel.
+    #[bisync]
+    pub async fn read_diff_data(
+        &mut self,
+        delay: &mut impl DelayNs,
+        out: &mut [i16],
+    ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        check_channel_count(out.len())?;
+        let mut buf = [0u16; FACTORY_MAX_CHANNELS];
+        let raw = &mut buf[..out.len()];
+        self.read_channels(FactoryDataType::Diff, delay, raw)
+            .await?;
+        for (value, &raw) in out.iter_mut().zip(raw.iter()) {
+            *value = raw as i16;
+        }
+        Ok(())
+    }
+
+    /// Baseline per channel, computed as raw minus diff from two consecutive
+    /// scans.
+    #[bisync]
+    pub async fn read_baseline_data(
+        &mut self,
+        delay: &mut impl DelayNs,
+        out: &mut [u16],
+    ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        check_channel_count(out.len())?;
+        let mut diff = [0i16; FACTORY_MAX_CHANNELS];
+        let diff = &mut diff[..out.len()];
+        self.read_raw_data(delay, out).await?;
+        self.read_diff_data(delay, diff).await?;
+        for (value, &diff) in out.iter_mut().zip(diff.iter()) {
+            *value = (i32::from(*value) - i32::from(diff)).clamp(0, i32::from(u16::MAX)) as u16;
+        }
+        Ok(())
+    }
+
+    /// Restore [`DeviceMode::Working`].
+    #[bisync]
+    pub async fn exit(mut self) -> Result<(), Ft6336uError<I2CBusErr>> {
+        self.active = false;
+        self.touch.write_device_mode(DeviceMode::Working).await
+    }
+}
+
+pub(super) fn check_channel_count<E>(len: usize) -> Result<(), Ft6336uError<E>> {
+    if len > FACTORY_MAX_CHANNELS {
+        return Err(Ft6336uError::NotSupported("Too many factory channels"));
+    }
+    Ok(())
+}
+
+#[only_sync]
+impl<I2CImpl, I2CBusErr, State> Drop for FactoryMode<'_, I2CImpl, I2CBusErr, State>
+where
+    I2CImpl: RegisterInterface<AddressType = u8, Error = Ft6336uError<I2CBusErr>>,
+    I2CBusErr: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState>,
+{
+    fn drop(&mut self) {
+        if self.active && self.touch.write_device_mode(DeviceMode::Working).is_err() {
+            warn!("Failed to restore working mode after factory mode");
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! Host-side rejection stages for `scan()` output.
+//!
+//! Filters take a [`TouchData`] and return a copy with rejected contacts
+//! reported as [`TouchStatus::Release`] (or, for [`PalmFilter`], optionally
+//! just flagged). New contacts are held back, also reported as `Release`,
+//! until they are classified, and are then forwarded starting with
+//! [`TouchStatus::Touch`]. A rejected contact is therefore never seen
+//! downstream, and a forwarded one is never turned into a release before the
+//! controller reports it lifted, which consumers would take for a tap.
+
+use crate::{TouchData, TouchStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct WaterFilterConfig {
+    /// Contacts at least this large (4-bit area)...
+    pub min_water_area: u8,
+    /// ...with at most this weight look like a water film.
+    pub max_water_weight: u8,
+    /// Weight change between two frames above this marks a contact unstable.
+    pub max_weight_delta: u8,
+    /// Number of frames after touch-down during which a new contact is held
+    /// back and checked for shape and instability. Contacts lifted earlier
+    /// are dropped; forwarded contacts are not checked again.
+    pub settle_frames: u8,
+}
+
+impl Default for WaterFilterConfig {
+    fn default() -> Self {
+        Self {
+            min_water_area: 8,
+            max_water_weight: 20,
+            max_weight_delta: 30,
+            settle_frames: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct WaterFilterStats {
+    /// Contacts seen (touch-downs).
+    pub contacts: u32,
+    /// Contacts rejected for a large, weak footprint.
+    pub rejected_shape: u32,
+    /// Contacts rejected for an unstable weight while settling.
+    pub rejected_unstable: u32,
+}
+
+impl WaterFilterStats {
+    pub fn rejected(&self) -> u32 {
+        self.rejected_shape + self.rejected_unstable
+    }
+}
+
+/// Suppresses water-like contacts, e.g. rain on an outdoor unit.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WaterFilter {
+    config: WaterFilterConfig,
+    stats: WaterFilterStats,
+    suppressed: [bool; 2],
+    forwarded: [bool; 2],
+    age: [u8; 2],
+    last_weight: [u8; 2],
+}
+
+impl WaterFilter {
+    pub fn new(config: WaterFilterConfig) -> Self {
+        Self {
+            config,
+            stats: WaterFilterStats::default(),
+            suppressed: [false; 2],
+            forwarded: [false; 2],
+            age: [0; 2],
+            last_weight: [0; 2],
+        }
+    }
+
+    pub fn config(&self) -> &WaterFilterConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: WaterFilterConfig) {
+        self.config = config;
+    }
+
+    pub fn stats(&self) -> &WaterFilterStats {
+        &self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = WaterFilterStats::default();
+    }
+
+    pub fn apply(&mut self, data: &TouchData) -> TouchData {
+        let mut out = *data;
+        let mut suppressed_any = false;
+        for (id, point) in out.points.iter_mut().enumerate() {
+            if point.status == TouchStatus::Release {
+                self.suppressed[id] = false;
+                self.forwarded[id] = false;
+                self.age[id] = 0;
+                continue;
+            }
+            if point.status == TouchStatus::Touch {
+                self.stats.contacts += 1;
+                self.age[id] = 0;
+            } else {
+                self.age[id] = self.age[id].saturating_add(1);
+            }
+
+            if !self.forwarded[id] && !self.suppressed[id] {
+                let water_shape = point.area >= self.config.min_water_area
+                    && point.weight <= self.config.max_water_weight;
+                let unstable = self.age[id] > 0
+                    && point.weight.abs_diff(self.last_weight[id]) > self.config.max_weight_delta;
+                if water_shape {
+                    self.stats.rejected_shape += 1;
+                } else if unstable {
+                    self.stats.rejected_unstable += 1;
+                }
+                self.suppressed[id] = water_shape || unstable;
+            }
+            self.last_weight[id] = point.weight;
+
+            if self.forwarded[id] {
+                continue;
+            }
+            if self.suppressed[id] || self.age[id] < self.config.settle_frames {
+                point.status = TouchStatus::Release;
+                suppressed_any = true;
+            } else {
+                self.forwarded[id] = true;
+                point.status = TouchStatus::Touch;
+            }
+        }
+        if suppressed_any {
+            recount(&mut out);
+        }
+        out
+    }
+}
+
+impl Default for WaterFilter {
+    fn default() -> Self {
+        Self::new(WaterFilterConfig::default())
+    }
+}
+
+/// What [`PalmFilter`] does with palm-like contacts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub enum PalmAction {
+    /// Report them as released.
+    #[default]
+    Drop,
+    /// Pass them through with [`TouchPoint::palm`](crate::TouchPoint::palm)
+    /// set.
+    Flag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct PalmFilterConfig {
+    /// Contacts with an area (4-bit) above this are palms.
+    pub max_area: u8,
+    /// Contacts with a weight above this are palms.
+    pub max_weight: u8,
+    /// Also reject contacts that touch down while a palm is on the panel,
+    /// e.g. a fingertip or knuckle of the resting hand.
+    pub reject_companions: bool,
+    /// Number of frames a new contact is held back before it is forwarded.
+    /// A palm often looks like a finger on its first frame; contacts lifted
+    /// earlier are dropped.
+    pub hold_frames: u8,
+    pub action: PalmAction,
+}
+
+impl Default for PalmFilterConfig {
+    fn default() -> Self {
+        Self {
+            max_area: 10,
+            max_weight: 200,
+            reject_companions: true,
+            hold_frames: 2,
+            action: PalmAction::Drop,
+        }
+    }
+}
+
+/// Rejects palms and other large contacts, e.g. a hand resting on a tablet.
+///
+/// A contact that only grows palm-sized after it was forwarded is flagged
+/// with [`TouchPoint::palm`](crate::TouchPoint::palm) in either mode but
+/// keeps streaming until it lifts.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PalmFilter {
+    config: PalmFilterConfig,
+    palm: [bool; 2],
+    forwarded: [bool; 2],
+    age: [u8; 2],
+    rejected: u32,
+}
+
+impl PalmFilter {
+    pub fn new(config: PalmFilterConfig) -> Self {
+        Self {
+            config,
+            palm: [false; 2],
+            forwarded: [false; 2],
+            age: [0; 2],
+            rejected: 0,
+        }
+    }
+
+    pub fn config(&self) -> &PalmFilterConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: PalmFilterConfig) {
+        self.config = config;
+    }
+
+    /// Number of contacts rejected so far.
+    pub fn rejected(&self) -> u32 {
+        self.rejected
+    }
+
+    pub fn apply(&mut self, data: &TouchData) -> TouchData {
+        let mut out = *data;
+        for (id, point) in out.points.iter().enumerate() {
+            if point.status == TouchStatus::Release {
+                self.palm[id] = false;
+                self.forwarded[id] = false;
+                self.age[id] = 0;
+                continue;
+            }
+            if point.status == TouchStatus::Touch {
+                self.age[id] = 0;
+            } else {
+                self.age[id] = self.age[id].saturating_add(1);
+            }
+            if !self.palm[id]
+                && (point.area > self.config.max_area || point.weight > self.config.max_weight)
+            {
+                self.palm[id] = true;
+                if !self.forwarded[id] {
+                    self.rejected += 1;
+                }
+            }
+        }
+        if self.config.reject_companions && self.palm.contains(&true) {
+            for (id, point) in out.points.iter().enumerate() {
+                // Only contacts still held back; a forwarded one is a real touch.
+                if point.status != TouchStatus::Release && !self.forwarded[id] && !self.palm[id] {
+                    self.palm[id] = true;
+                    self.rejected += 1;
+                }
+            }
+        }
+
+        let mut suppressed_any = false;
+        for (id, point) in out.points.iter_mut().enumerate() {
+            if point.status == TouchStatus::Release {
+                continue;
+            }
+            if self.palm[id] {
+                point.palm = true;
+            }
+            if self.forwarded[id] {
+                continue;
+            }
+            let dropped = self.palm[id] && self.config.action == PalmAction::Drop;
+            if dropped || self.age[id] < self.config.hold_frames {
+                point.status = TouchStatus::Release;
+                suppressed_any = true;
+            } else {
+                self.forwarded[id] = true;
+                point.status = TouchStatus::Touch;
+            }
+        }
+        if suppressed_any {
+            recount(&mut out);
+        }
+        out
+    }
+}
+
+impl Default for PalmFilter {
+    fn default() -> Self {
+        Self::new(PalmFilterConfig::default())
+    }
+}
+
+/// Recompute `touch_count` after contacts were suppressed.
+fn recount(data: &mut TouchData) {
+    data.touch_count = data
+        .points
+        .iter()
+        .filter(|p| p.status != TouchStatus::Release)
+        .count() as u8;
+}
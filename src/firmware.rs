@@ -0,0 +1,68 @@
+//! Types for the FocalTech I2C bootloader upgrade flow (`upgrade_firmware()`).
+
+use thiserror::Error;
+
+/// Largest payload of one flash write packet.
+pub const UPGRADE_PACKET_SIZE: usize = 128;
+
+/// Bootloader parameters. The defaults match the FT6x36 family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct BootloaderConfig {
+    /// ID the bootloader answers to the `0x90` read-ID command.
+    pub boot_id: [u8; 2],
+    /// Time for the chip to reset into the bootloader after `0xAA`/`0x55`.
+    pub reset_delay_ms: u32,
+    /// Time for erasing the application flash.
+    pub erase_delay_ms: u32,
+    /// Time for programming one packet.
+    pub packet_delay_ms: u32,
+    /// Time for the application to start after the final reset.
+    pub boot_delay_ms: u32,
+}
+
+impl Default for BootloaderConfig {
+    fn default() -> Self {
+        Self {
+            boot_id: [0x79, 0x18],
+            reset_delay_ms: 30,
+            erase_delay_ms: 2000,
+            packet_delay_ms: 20,
+            boot_delay_ms: 300,
+        }
+    }
+}
+
+/// Upgrade stage reported to the progress callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum UpgradeProgress {
+    EnteringBootloader,
+    Erasing,
+    Writing { written: usize, total: usize },
+    Verifying,
+    Resetting,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum UpgradeError {
+    #[error("Firmware image is empty")]
+    EmptyImage,
+    #[error("Firmware image exceeds the bootloader address range")]
+    ImageTooLarge,
+    #[error("Unexpected boot ID {found:02x?}, expected {expected:02x?}")]
+    BootIdMismatch { expected: [u8; 2], found: [u8; 2] },
+    #[error("Checksum mismatch: image {expected:#04x}, flash {found:#04x}")]
+    ChecksumMismatch { expected: u8, found: u8 },
+}
+
+/// Checksum the bootloader reports for a flashed image: XOR of all bytes.
+pub fn checksum(image: &[u8]) -> u8 {
+    image.iter().fold(0, |acc, &b| acc ^ b)
+}
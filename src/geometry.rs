@@ -0,0 +1,96 @@
+//! Shapes in panel coordinates for hit-testing.
+
+/// Axis-aligned rectangle. `x`/`y` is the top-left corner; the right and
+/// bottom edges are exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Rect {
+    pub const fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x >= self.x
+            && y >= self.y
+            && u32::from(x) < u32::from(self.x) + u32::from(self.width)
+            && u32::from(y) < u32::from(self.y) + u32::from(self.height)
+    }
+}
+
+/// Circle around `(cx, cy)`; points on the edge are inside.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct Circle {
+    pub cx: u16,
+    pub cy: u16,
+    pub radius: u16,
+}
+
+impl Circle {
+    pub const fn new(cx: u16, cy: u16, radius: u16) -> Self {
+        Self { cx, cy, radius }
+    }
+
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        // Two squared u16 distances overflow u32.
+        let dx = u64::from(x.abs_diff(self.cx));
+        let dy = u64::from(y.abs_diff(self.cy));
+        let r = u64::from(self.radius);
+        dx * dx + dy * dy <= r * r
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub enum Shape {
+    Rect(Rect),
+    Circle(Circle),
+}
+
+impl Shape {
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        match self {
+            Shape::Rect(rect) => rect.contains(x, y),
+            Shape::Circle(circle) => circle.contains(x, y),
+        }
+    }
+}
+
+impl From<Rect> for Shape {
+    fn from(rect: Rect) -> Self {
+        Shape::Rect(rect)
+    }
+}
+
+impl From<Circle> for Shape {
+    fn from(circle: Circle) -> Self {
+        Shape::Circle(circle)
+    }
+}
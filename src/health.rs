@@ -0,0 +1,91 @@
+//! Result types for `self_test()`.
+//!
+//! The self-test checks I2C reachability, chip identity, firmware version and,
+//! in factory mode, per-channel raw data and noise. Channel results are bit
+//! masks indexed by channel number, which fits [`crate::FACTORY_MAX_CHANNELS`].
+
+/// Value of `ChipId` on a genuine FT6336U.
+pub const FT6336U_CHIP_ID: u8 = 0x64;
+
+/// Pass/fail limits for `self_test()`.
+///
+/// Raw and noise limits depend on the panel and cover lens, so the defaults
+/// are deliberately wide. Characterize a few known-good units and tighten
+/// them for production use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct SelfTestLimits {
+    /// Lowest acceptable `FirmwareId`.
+    pub min_firmware_id: u8,
+    /// Raw counts below this mark a channel as open.
+    pub raw_min: u16,
+    /// Raw counts above this mark a channel as shorted.
+    pub raw_max: u16,
+    /// Highest acceptable peak-to-peak diff on any channel.
+    pub max_noise: u16,
+    /// Number of diff frames sampled for the noise check. 0 skips it.
+    pub noise_frames: u8,
+}
+
+impl Default for SelfTestLimits {
+    fn default() -> Self {
+        Self {
+            min_firmware_id: 0,
+            raw_min: 1000,
+            raw_max: 30000,
+            max_noise: 100,
+            noise_frames: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct HealthReport {
+    /// The controller answered on the bus. All other fields are only
+    /// meaningful if this is set.
+    pub reachable: bool,
+    pub chip_id: u8,
+    pub firmware_id: u8,
+    pub library_version: u16,
+    /// `firmware_id` is at least [`SelfTestLimits::min_firmware_id`].
+    pub firmware_ok: bool,
+    /// Channels reported by the chip (TX + RX).
+    pub channel_count: usize,
+    /// Bit `n` is set if channel `n` read below [`SelfTestLimits::raw_min`].
+    pub open_channels: u64,
+    /// Bit `n` is set if channel `n` read above [`SelfTestLimits::raw_max`].
+    pub short_channels: u64,
+    /// Worst peak-to-peak diff over the sampled frames, if sampled.
+    pub noise: Option<u16>,
+    /// Channel with the worst noise.
+    pub noisiest_channel: Option<usize>,
+    /// `noise` is within [`SelfTestLimits::max_noise`] (or was not sampled).
+    pub noise_ok: bool,
+}
+
+impl HealthReport {
+    pub fn chip_id_ok(&self) -> bool {
+        self.chip_id == FT6336U_CHIP_ID
+    }
+
+    /// Every check passed.
+    pub fn passed(&self) -> bool {
+        self.reachable
+            && self.chip_id_ok()
+            && self.firmware_ok
+            && self.open_channels == 0
+            && self.short_channels == 0
+            && self.noise_ok
+    }
+}
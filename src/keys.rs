@@ -0,0 +1,169 @@
+//! Virtual keys printed on the panel outside the display area.
+//!
+//! [`VirtualKeys`] maps rectangles in panel coordinates to key codes. Contacts
+//! that touch down inside a key zone belong to the key layer until they lift:
+//! they produce debounced [`KeyEvent`]s and are removed from the pointer
+//! stream, even if they slide out of the zone.
+
+use heapless::Vec;
+
+use crate::geometry::Rect;
+use crate::{TouchData, TouchStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct VirtualKey {
+    pub zone: Rect,
+    pub code: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub enum KeyEvent {
+    Pressed(u16),
+    Released(u16),
+}
+
+/// Result of [`VirtualKeys::process`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct KeyFrame<const N: usize> {
+    /// The input with key contacts reported as released.
+    pub pointer: TouchData,
+    /// Key events of this frame, at most one per key.
+    pub events: Vec<KeyEvent, N>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct KeyState {
+    pressed: bool,
+    /// Consecutive frames touched.
+    held: u8,
+    /// Consecutive frames untouched while pressed and its contact is down.
+    gap: u8,
+}
+
+/// Up to `N` virtual keys.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VirtualKeys<const N: usize> {
+    keys: Vec<(VirtualKey, KeyState), N>,
+    debounce_frames: u8,
+    owned: [bool; 2],
+    /// Key each owned contact was last seen on.
+    last_key: [Option<u16>; 2],
+}
+
+impl<const N: usize> VirtualKeys<N> {
+    /// A key is pressed after being touched for `debounce_frames` consecutive
+    /// frames. It is released as soon as its contact lifts, or after as many
+    /// untouched frames while the contact stays down, e.g. sliding off the
+    /// zone. 1 reacts immediately.
+    pub fn new(debounce_frames: u8) -> Self {
+        Self {
+            keys: Vec::new(),
+            debounce_frames: debounce_frames.max(1),
+            owned: [false; 2],
+            last_key: [None; 2],
+        }
+    }
+
+    /// Add a key. Returns it back if the map is full.
+    pub fn add(&mut self, zone: Rect, code: u16) -> Result<(), VirtualKey> {
+        let key = VirtualKey { zone, code };
+        self.keys
+            .push((key, KeyState::default()))
+            .map_err(|(key, _)| key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &VirtualKey> {
+        self.keys.iter().map(|(key, _)| key)
+    }
+
+    /// Code of the key zone containing a point.
+    pub fn hit(&self, x: u16, y: u16) -> Option<u16> {
+        self.keys()
+            .find(|key| key.zone.contains(x, y))
+            .map(|key| key.code)
+    }
+
+    /// Feed one `scan()` result.
+    pub fn process(&mut self, data: &TouchData) -> KeyFrame<N> {
+        let mut pointer = *data;
+        let mut touched: Vec<u16, 2> = Vec::new();
+        let mut any_owned = false;
+
+        for (id, point) in pointer.points.iter_mut().enumerate() {
+            match point.status {
+                TouchStatus::Release => {
+                    self.owned[id] = false;
+                    self.last_key[id] = None;
+                    continue;
+                }
+                TouchStatus::Touch => self.owned[id] = self.hit(point.x, point.y).is_some(),
+                TouchStatus::Stream => {}
+            }
+            if self.owned[id] {
+                if let Some(code) = self.hit(point.x, point.y) {
+                    // Capacity 2 matches the point count.
+                    let _ = touched.push(code);
+                    self.last_key[id] = Some(code);
+                }
+                point.status = TouchStatus::Release;
+                any_owned = true;
+            }
+        }
+        if any_owned {
+            pointer.touch_count = pointer
+                .points
+                .iter()
+                .filter(|p| p.status != TouchStatus::Release)
+                .count() as u8;
+        }
+
+        let mut events = Vec::new();
+        for (key, state) in self.keys.iter_mut() {
+            let mut event = None;
+            if touched.contains(&key.code) {
+                state.gap = 0;
+                state.held = state.held.saturating_add(1);
+                if !state.pressed && state.held >= self.debounce_frames {
+                    state.pressed = true;
+                    event = Some(KeyEvent::Pressed(key.code));
+                }
+            } else {
+                state.held = 0;
+                if state.pressed {
+                    // Debounce gaps only while the contact is still down. Once
+                    // it lifts no further frames may arrive, e.g. with INT
+                    // triggered scanning, so release right away.
+                    let down = self.last_key.contains(&Some(key.code));
+                    state.gap = state.gap.saturating_add(1);
+                    if !down || state.gap >= self.debounce_frames {
+                        state.pressed = false;
+                        state.gap = 0;
+                        event = Some(KeyEvent::Released(key.code));
+                    }
+                }
+            }
+            if let Some(event) = event {
+                debug!("Virtual key {:?}", event);
+                // One event per key, and `events` holds `N`.
+                let _ = events.push(event);
+            }
+        }
+
+        KeyFrame { pointer, events }
+    }
+}
@@ -3,10 +3,35 @@
 #[macro_use]
 pub(crate) mod fmt;
 
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod clock;
+pub mod desktop;
+#[cfg(feature = "embassy")]
+pub mod embassy;
+pub mod filter;
+pub mod firmware;
+pub mod geometry;
+pub mod health;
+pub mod keys;
+pub mod motion;
+pub mod noise;
+pub mod quirks;
+pub mod rate;
+pub mod regions;
+pub mod registers;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "futures")]
+pub mod stream;
+pub mod tuning;
+
 use thiserror::Error;
 
 device_driver::create_device!(device_name: Ft6336uLowLevel, manifest: "device.yaml");
 pub const FT6336U_I2C_ADDRESS: u8 = 0x38;
+/// Upper bound on sensor channels read in factory mode.
+pub const FACTORY_MAX_CHANNELS: usize = 64;
 
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -15,22 +40,68 @@ pub enum Ft6336uError<I2cErr> {
     I2c(I2cErr),
     #[error("Not supported: {0}")]
     NotSupported(&'static str),
+    #[error("Timeout: {0}")]
+    Timeout(&'static str),
+    #[error("Firmware upgrade failed: {0}")]
+    Upgrade(#[from] firmware::UpgradeError),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
 pub enum TouchStatus {
     Touch,
     Stream,
     Release,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Proximity (face detection) result, reported while `enable_proximity(true)`
+/// is in effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub enum ProximityState {
+    Near,
+    Far,
+}
+
+impl ProximityState {
+    /// Proximity result held in the gesture ID, if any. Other values show
+    /// up in between proximity changes and carry no result.
+    pub fn from_gesture(gesture: GestureId) -> Option<Self> {
+        match gesture {
+            GestureId::ProximityNear => Some(Self::Near),
+            GestureId::ProximityFar => Some(Self::Far),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
 pub struct TouchPoint {
     pub status: TouchStatus,
     pub x: u16,
     pub y: u16,
+    /// Touch weight (signal strength) as reported by the controller.
+    pub weight: u8,
+    /// Touch area, 4 bits.
+    pub area: u8,
+    /// Set by [`filter::PalmFilter`] for palm-like contacts.
+    pub palm: bool,
 }
 
 impl Default for TouchPoint {
@@ -39,15 +110,202 @@ impl Default for TouchPoint {
             status: TouchStatus::Release,
             x: 0,
             y: 0,
+            weight: 0,
+            area: 0,
+            palm: false,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
 pub struct TouchData {
     pub touch_count: u8,
     pub points: [TouchPoint; 2],
+    /// `None` unless proximity detection is enabled.
+    pub proximity: Option<ProximityState>,
+}
+
+/// A `scan()` result with the time it was read and its sequence number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct TouchFrame {
+    pub data: TouchData,
+    /// [`clock::Clock`] reading taken right after the burst read.
+    pub timestamp_us: u64,
+    /// [`RawFrame::sequence`] of the burst this was decoded from. A gap
+    /// between consecutive frames means frames were dropped on the way.
+    pub sequence: u32,
+}
+
+/// One burst read of registers 0x01-0x0E, numbered when it was read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct RawFrame {
+    /// The registers exactly as returned:
+    ///
+    /// - `bytes[0]`:      GestureId (proximity result in face detection mode)
+    /// - `bytes[1]`:      TdStatus (touch count in bits 3:0)
+    /// - `bytes[2..8]`:   Touch point 0: XEvent(2B) + YId(2B) + Weight(1B) + Misc(1B)
+    /// - `bytes[8..14]`:  Touch point 1: XEvent(2B) + YId(2B) + Weight(1B) + Misc(1B)
+    pub bytes: [u8; 14],
+    /// Number of bursts read before this one by the same driver (wrapping),
+    /// stamped by `read_raw_frame()`. Frames lost after the read, e.g. in a
+    /// full [`RawFrameQueue`], show up as a gap.
+    pub sequence: u32,
+}
+
+/// Lock-free single-producer/single-consumer ring of raw frames, holding up to
+/// `N - 1` frames. Read frames with `read_raw_frame()` in the interrupt
+/// handler, decode them later with [`Ft6336uState::update`].
+pub type RawFrameQueue<const N: usize> = heapless::spsc::Queue<RawFrame, N>;
+pub type RawFrameProducer<'a, const N: usize> = heapless::spsc::Producer<'a, RawFrame, N>;
+pub type RawFrameConsumer<'a, const N: usize> = heapless::spsc::Consumer<'a, RawFrame, N>;
+
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Ft6336uState {
+    touch_data: TouchData,
+    quirks: Quirks,
+    proximity_enabled: bool,
+    sequence: u32,
+}
+
+impl Ft6336uState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn touch_data(&self) -> TouchData {
+        self.touch_data
+    }
+
+    /// Sequence number the next frame read by `read_raw_frame()` will get.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Take the next read sequence number.
+    pub(crate) fn next_sequence(&mut self) -> u32 {
+        let sequence = self.sequence;
+        self.sequence = sequence.wrapping_add(1);
+        sequence
+    }
+
+    /// Workarounds applied to decoded frames and register writes.
+    pub fn quirks(&self) -> &Quirks {
+        &self.quirks
+    }
+
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    /// Decode the proximity result in [`TouchData::proximity`]. Set by
+    /// `enable_proximity()`; only needs calling directly when decoding frames
+    /// without a driver.
+    pub fn set_proximity_enabled(&mut self, enabled: bool) {
+        self.proximity_enabled = enabled;
+        if !enabled {
+            self.touch_data.proximity = None;
+        }
+    }
+
+    /// Decode a raw frame and advance the Touch/Stream/Release tracking.
+    pub fn update(&mut self, frame: &RawFrame) -> TouchData {
+        // XEvent (BE 16-bit): event = bits 15:14 (high[7:6]), x = bits 11:0 (high[3:0] << 8 | low)
+        // YId    (BE 16-bit): id    = bits 15:12 (high[7:4]), y = bits 11:0 (high[3:0] << 8 | low)
+        let buf = &frame.bytes;
+        let quirks = self.quirks;
+        let touch_data = &mut self.touch_data;
+
+        if self.proximity_enabled {
+            // Gesture IDs without a proximity result keep the last one.
+            if let Some(proximity) = ProximityState::from_gesture(GestureId::from(buf[0])) {
+                touch_data.proximity = Some(proximity);
+            }
+        }
+
+        let touch_count = buf[1] & 0x0F;
+        touch_data.touch_count = touch_count;
+
+        if touch_count == 0 {
+            touch_data.points[0].status = TouchStatus::Release;
+            touch_data.points[1].status = TouchStatus::Release;
+        } else {
+            let count = core::cmp::min(touch_count as usize, 2);
+            let mut seen = [false; 2];
+
+            for i in 0..count {
+                let off = 2 + i * 6;
+                let id = ((buf[off + 2] >> 4) & 0x0F) as usize;
+                if id < 2 {
+                    seen[id] = true;
+                    let x = (((buf[off] & 0x0F) as u16) << 8) | (buf[off + 1] as u16);
+                    let y = (((buf[off + 2] & 0x0F) as u16) << 8) | (buf[off + 3] as u16);
+                    let (x, y) = quirks.correct(x, y);
+
+                    let prev_status = touch_data.points[id].status;
+                    touch_data.points[id].status = match prev_status {
+                        TouchStatus::Release => TouchStatus::Touch,
+                        _ => TouchStatus::Stream,
+                    };
+                    touch_data.points[id].x = x;
+                    touch_data.points[id].y = y;
+                    touch_data.points[id].weight = buf[off + 4];
+                    touch_data.points[id].area = buf[off + 5] >> 4;
+                }
+            }
+
+            for (id, &was_seen) in seen.iter().enumerate() {
+                if !was_seen {
+                    touch_data.points[id].status = TouchStatus::Release;
+                }
+            }
+        }
+
+        *touch_data
+    }
+
+    /// Borrow `i2c` for the duration of the returned driver, keeping touch
+    /// tracking state in `self` between calls.
+    pub fn attach<'a, I2CBus, E>(
+        &'a mut self,
+        i2c: &'a mut I2CBus,
+    ) -> Ft6336u<Ft6336uInterface<&'a mut I2CBus>, E, &'a mut Self>
+    where
+        I2CBus: embedded_hal::i2c::I2c<Error = E>,
+        E: core::fmt::Debug,
+    {
+        Ft6336u::with_state(i2c, self)
+    }
+
+    /// Async counterpart of [`Ft6336uState::attach`].
+    pub fn attach_async<'a, I2CBus, E>(
+        &'a mut self,
+        i2c: &'a mut I2CBus,
+    ) -> Ft6336uAsync<Ft6336uInterface<&'a mut I2CBus>, E, &'a mut Self>
+    where
+        I2CBus: embedded_hal_async::i2c::I2c<Error = E>,
+        E: core::fmt::Debug,
+    {
+        Ft6336uAsync::with_state(i2c, self)
+    }
 }
 
 pub struct Ft6336uInterface<I2CBus> {
@@ -58,25 +316,74 @@ impl<I2CBus> Ft6336uInterface<I2CBus> {
     pub fn new(i2c_bus: I2CBus) -> Self {
         Self { i2c_bus }
     }
+
+    pub fn i2c(&self) -> &I2CBus {
+        &self.i2c_bus
+    }
+
+    pub fn i2c_mut(&mut self) -> &mut I2CBus {
+        &mut self.i2c_bus
+    }
+
+    pub fn into_inner(self) -> I2CBus {
+        self.i2c_bus
+    }
 }
 
 #[path = "."]
 mod asynchronous {
     use bisync::asynchronous::*;
     use device_driver::AsyncRegisterInterface as RegisterInterface;
+    use embedded_hal_async::delay::DelayNs;
     use embedded_hal_async::i2c::I2c;
     mod driver;
+    mod factory;
+    mod panels;
+    mod self_test;
+    mod upgrade;
     pub use driver::*;
+    pub use factory::*;
+    pub use panels::*;
 }
-pub use asynchronous::Ft6336u as Ft6336uAsync;
+pub use asynchronous::{
+    FactoryMode as FactoryModeAsync, Ft6336u as Ft6336uAsync, Panels as PanelsAsync,
+};
 
 #[path = "."]
 mod blocking {
     use bisync::synchronous::*;
     use device_driver::RegisterInterface;
+    use embedded_hal::delay::DelayNs;
     use embedded_hal::i2c::I2c;
     #[allow(clippy::duplicate_mod)]
     mod driver;
+    #[allow(clippy::duplicate_mod)]
+    mod factory;
+    #[allow(clippy::duplicate_mod)]
+    mod panels;
+    #[allow(clippy::duplicate_mod)]
+    mod self_test;
+    #[allow(clippy::duplicate_mod)]
+    mod upgrade;
     pub use driver::*;
+    pub use factory::*;
+    pub use panels::*;
 }
-pub use blocking::Ft6336u;
+pub use blocking::{FactoryMode, Ft6336u, Panels};
+#[cfg(feature = "embassy-time")]
+pub use clock::EmbassyClock;
+pub use clock::{Clock, ManualClock};
+pub use desktop::{DesktopFrame, DesktopPoint, PanelError, PanelTransform, Rotation};
+pub use filter::{
+    PalmAction, PalmFilter, PalmFilterConfig, WaterFilter, WaterFilterConfig, WaterFilterStats,
+};
+pub use firmware::{BootloaderConfig, UpgradeError, UpgradeProgress};
+pub use geometry::{Circle, Rect, Shape};
+pub use health::{FT6336U_CHIP_ID, HealthReport, SelfTestLimits};
+pub use keys::{KeyEvent, KeyFrame, VirtualKey, VirtualKeys};
+pub use motion::{Motion, MotionConfig, MotionTracker, Vector};
+pub use noise::{NoiseMonitor, NoiseMonitorConfig};
+pub use quirks::{FirmwareIdentity, QuirkEntry, Quirks};
+pub use rate::{RateMonitor, RateStats};
+pub use regions::{Region, RegionEvent, RegionMap};
+pub use tuning::{ThresholdTuner, TunerConfig, TuningPreset};
@@ -0,0 +1,317 @@
+//! Per-ID velocity and acceleration tracking for kinetic scrolling.
+//!
+//! [`MotionTracker`] keeps a short position history for each touch ID, fed by
+//! `scan()` results and a monotonic timestamp in microseconds. Velocity and
+//! acceleration come from a quadratic least-squares fit over the most recent
+//! samples, like Android's `VelocityTracker`. When a contact lifts, the
+//! velocity at that moment is kept as its fling velocity.
+//!
+//! ```rust,ignore
+//! let mut tracker = MotionTracker::default();
+//! loop {
+//!     let data = touch.scan()?;
+//!     tracker.update(&data, now_us());
+//!     if let Some(v) = tracker.take_fling(0) {
+//!         list.fling(v.x, v.y);
+//!     }
+//! }
+//! ```
+
+use crate::{TouchData, TouchFrame, TouchStatus};
+
+/// Samples kept per touch ID.
+pub const MOTION_HISTORY: usize = 8;
+
+/// A 2D vector, in pixels per second (velocity) or pixels per second squared
+/// (acceleration).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct Vector {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vector {
+    pub fn magnitude_squared(&self) -> f32 {
+        self.x * self.x + self.y * self.y
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct Motion {
+    pub velocity: Vector,
+    pub acceleration: Vector,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct MotionConfig {
+    /// Samples older than this (relative to the newest) are ignored.
+    pub horizon_us: u64,
+    /// No fling if the contact rested this long before lifting.
+    pub max_pause_us: u64,
+    /// Fling velocities below this are reported as no fling.
+    pub min_fling_velocity: u16,
+    /// Fling velocities are clamped to this magnitude.
+    pub max_fling_velocity: u16,
+}
+
+impl Default for MotionConfig {
+    fn default() -> Self {
+        Self {
+            horizon_us: 100_000,
+            max_pause_us: 40_000,
+            min_fling_velocity: 50,
+            max_fling_velocity: 8000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Sample {
+    timestamp_us: u64,
+    x: u16,
+    y: u16,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct History {
+    samples: [Sample; MOTION_HISTORY],
+    /// Index of the next slot to write.
+    head: usize,
+    len: usize,
+    fling: Option<Vector>,
+}
+
+impl History {
+    fn push(&mut self, sample: Sample) {
+        self.samples[self.head] = sample;
+        self.head = (self.head + 1) % MOTION_HISTORY;
+        self.len = (self.len + 1).min(MOTION_HISTORY);
+    }
+
+    fn newest(&self) -> Option<&Sample> {
+        (self.len > 0).then(|| &self.samples[(self.head + MOTION_HISTORY - 1) % MOTION_HISTORY])
+    }
+
+    /// Samples newest first.
+    fn iter(&self) -> impl Iterator<Item = &Sample> {
+        (1..=self.len).map(|i| &self.samples[(self.head + MOTION_HISTORY - i) % MOTION_HISTORY])
+    }
+
+    fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MotionTracker {
+    config: MotionConfig,
+    history: [History; 2],
+}
+
+impl MotionTracker {
+    pub fn new(config: MotionConfig) -> Self {
+        Self {
+            config,
+            history: [History::default(); 2],
+        }
+    }
+
+    pub fn config(&self) -> &MotionConfig {
+        &self.config
+    }
+
+    /// Feed one `scan()` result taken at `timestamp_us` (monotonic).
+    pub fn update(&mut self, data: &TouchData, timestamp_us: u64) {
+        for (id, point) in data.points.iter().enumerate() {
+            let history = &mut self.history[id];
+            match point.status {
+                TouchStatus::Touch => {
+                    history.clear();
+                    history.fling = None;
+                    history.push(Sample {
+                        timestamp_us,
+                        x: point.x,
+                        y: point.y,
+                    });
+                }
+                TouchStatus::Stream => {
+                    if history
+                        .newest()
+                        .is_some_and(|s| s.timestamp_us >= timestamp_us)
+                    {
+                        continue;
+                    }
+                    history.push(Sample {
+                        timestamp_us,
+                        x: point.x,
+                        y: point.y,
+                    });
+                }
+                TouchStatus::Release => {
+                    if history.len == 0 {
+                        continue;
+                    }
+                    let paused = history.newest().is_some_and(|s| {
+                        timestamp_us.saturating_sub(s.timestamp_us) > self.config.max_pause_us
+                    });
+                    history.fling = if paused {
+                        None
+                    } else {
+                        fling_velocity(&self.config, fit(&self.config, history).velocity)
+                    };
+                    history.clear();
+                }
+            }
+        }
+    }
+
+    /// [`update`](Self::update) from a `scan_timestamped()` result.
+    pub fn update_frame(&mut self, frame: &TouchFrame) {
+        self.update(&frame.data, frame.timestamp_us);
+    }
+
+    /// Current motion of an active contact. `None` for released IDs.
+    pub fn motion(&self, id: usize) -> Option<Motion> {
+        let history = self.history.get(id)?;
+        (history.len > 0).then(|| fit(&self.config, history))
+    }
+
+    /// Velocity of the contact at the moment it lifted, if fast enough to
+    /// count as a fling. Cleared by the next touch-down of the same ID.
+    pub fn fling(&self, id: usize) -> Option<Vector> {
+        self.history.get(id)?.fling
+    }
+
+    /// Like [`fling`](Self::fling), but reports each fling only once.
+    pub fn take_fling(&mut self, id: usize) -> Option<Vector> {
+        self.history.get_mut(id)?.fling.take()
+    }
+}
+
+fn fling_velocity(config: &MotionConfig, velocity: Vector) -> Option<Vector> {
+    let speed_sq = velocity.magnitude_squared();
+    let min = f32::from(config.min_fling_velocity);
+    let max = f32::from(config.max_fling_velocity);
+    if speed_sq < min * min {
+        return None;
+    }
+    if speed_sq > max * max {
+        let scale = max / sqrt(speed_sq);
+        return Some(Vector {
+            x: velocity.x * scale,
+            y: velocity.y * scale,
+        });
+    }
+    Some(velocity)
+}
+
+// `f32::sqrt` needs std. Halving the exponent bits gives a seed within a few
+// percent for any positive finite value, so four Newton steps reach full
+// precision.
+fn sqrt(value: f32) -> f32 {
+    if value <= 0.0 || !value.is_finite() {
+        return value.max(0.0);
+    }
+    let mut x = f32::from_bits((value.to_bits() >> 1) + 0x1FBD_1DF5);
+    for _ in 0..4 {
+        x = 0.5 * (x + value / x);
+    }
+    x
+}
+
+/// Least-squares fit of position over time within the horizon: quadratic with
+/// three or more samples, linear with two.
+fn fit(config: &MotionConfig, history: &History) -> Motion {
+    let Some(newest) = history.newest() else {
+        return Motion::default();
+    };
+    let mut t = [0f32; MOTION_HISTORY];
+    let mut xs = [0f32; MOTION_HISTORY];
+    let mut ys = [0f32; MOTION_HISTORY];
+    let mut n = 0;
+    for sample in history.iter() {
+        let age = newest.timestamp_us - sample.timestamp_us;
+        if age > config.horizon_us {
+            break;
+        }
+        // Seconds and pixels relative to the newest sample, so velocity is
+        // the fit's linear term and the sums stay small enough for f32.
+        t[n] = -(age as f32) / 1_000_000.0;
+        xs[n] = f32::from(sample.x) - f32::from(newest.x);
+        ys[n] = f32::from(sample.y) - f32::from(newest.y);
+        n += 1;
+    }
+    let (t, xs, ys) = (&t[..n], &xs[..n], &ys[..n]);
+
+    match n {
+        0 | 1 => Motion::default(),
+        2 => {
+            let dt = t[0] - t[1];
+            Motion {
+                velocity: Vector {
+                    x: (xs[0] - xs[1]) / dt,
+                    y: (ys[0] - ys[1]) / dt,
+                },
+                acceleration: Vector::default(),
+            }
+        }
+        _ => {
+            let (bx, cx) = quadratic_fit(t, xs);
+            let (by, cy) = quadratic_fit(t, ys);
+            Motion {
+                velocity: Vector { x: bx, y: by },
+                acceleration: Vector {
+                    x: 2.0 * cx,
+                    y: 2.0 * cy,
+                },
+            }
+        }
+    }
+}
+
+/// Fit `v = a + b*t + c*t^2` and return `(b, c)`, solving the normal
+/// equations with Cramer's rule.
+fn quadratic_fit(t: &[f32], v: &[f32]) -> (f32, f32) {
+    let (mut s0, mut s1, mut s2, mut s3, mut s4) = (0f32, 0f32, 0f32, 0f32, 0f32);
+    let (mut v0, mut v1, mut v2) = (0f32, 0f32, 0f32);
+    for (&t, &v) in t.iter().zip(v) {
+        let t2 = t * t;
+        s0 += 1.0;
+        s1 += t;
+        s2 += t2;
+        s3 += t2 * t;
+        s4 += t2 * t2;
+        v0 += v;
+        v1 += v * t;
+        v2 += v * t2;
+    }
+    let det = s0 * (s2 * s4 - s3 * s3) - s1 * (s1 * s4 - s3 * s2) + s2 * (s1 * s3 - s2 * s2);
+    if det == 0.0 {
+        return (0.0, 0.0);
+    }
+    let det_b = s0 * (v1 * s4 - s3 * v2) - v0 * (s1 * s4 - s3 * s2) + s2 * (s1 * v2 - v1 * s2);
+    let det_c = s0 * (s2 * v2 - v1 * s3) - s1 * (s1 * v2 - v1 * s2) + v0 * (s1 * s3 - s2 * s2);
+    (det_b / det, det_c / det)
+}
@@ -0,0 +1,139 @@
+//! Ghost-touch detection for charger noise.
+//!
+//! Common-mode noise from a USB charger shows up as touches that appear and
+//! vanish within a frame or two, or that jump across the panel between
+//! consecutive reports. [`NoiseMonitor`] counts those signatures in the
+//! `scan()` output and, once they cross a threshold, asks for frequency
+//! hopping (`set_charger_mode(true)`). `scan_with_noise_monitor()` wires it
+//! into scanning.
+
+use crate::{TouchData, TouchStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct NoiseMonitorConfig {
+    /// Contacts released after fewer frames than this count as ghosts.
+    pub min_contact_frames: u8,
+    /// Movement between two consecutive frames (per axis) above this counts
+    /// as an impossible jump.
+    pub max_jump: u16,
+    /// Number of ghost events within `window_frames` that triggers hopping.
+    pub trigger_events: u8,
+    /// Length of the observation window in frames.
+    pub window_frames: u16,
+}
+
+impl Default for NoiseMonitorConfig {
+    fn default() -> Self {
+        Self {
+            min_contact_frames: 2,
+            max_jump: 200,
+            trigger_events: 4,
+            window_frames: 100,
+        }
+    }
+}
+
+/// Ghost-touch signature seen by the monitor, for logging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GhostEvent {
+    /// A contact lifted after fewer than `min_contact_frames` frames.
+    ShortContact { id: usize },
+    /// A contact moved further than `max_jump` in one frame.
+    Jump { id: usize },
+}
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoiseMonitor {
+    config: NoiseMonitorConfig,
+    contact_frames: [u8; 2],
+    last: TouchData,
+    events: u8,
+    window: u16,
+    triggered: bool,
+}
+
+impl NoiseMonitor {
+    pub fn new(config: NoiseMonitorConfig) -> Self {
+        Self {
+            config,
+            contact_frames: [0; 2],
+            last: TouchData::default(),
+            events: 0,
+            window: 0,
+            triggered: false,
+        }
+    }
+
+    pub fn config(&self) -> &NoiseMonitorConfig {
+        &self.config
+    }
+
+    /// Hopping has been requested and not yet [`reset`](Self::reset).
+    pub fn is_triggered(&self) -> bool {
+        self.triggered
+    }
+
+    /// Start monitoring afresh, e.g. after the charger was unplugged and
+    /// frequency hopping was turned off again.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    /// Feed one `scan()` result. Returns `true` exactly once, on the frame
+    /// where the ghost events in the current window reach the threshold.
+    pub fn observe(&mut self, data: &TouchData) -> bool {
+        for (id, point) in data.points.iter().enumerate() {
+            if let Some(event) = self.classify(id, point.status, point.x, point.y) {
+                debug!("Ghost touch: {:?}", event);
+                self.events = self.events.saturating_add(1);
+            }
+        }
+        self.last = *data;
+
+        let trigger = !self.triggered && self.events >= self.config.trigger_events;
+        self.triggered |= trigger;
+
+        self.window += 1;
+        if self.window >= self.config.window_frames {
+            self.window = 0;
+            self.events = 0;
+        }
+        trigger
+    }
+
+    fn classify(&mut self, id: usize, status: TouchStatus, x: u16, y: u16) -> Option<GhostEvent> {
+        let last = self.last.points[id];
+        match status {
+            TouchStatus::Touch => {
+                self.contact_frames[id] = 1;
+                None
+            }
+            TouchStatus::Stream => {
+                self.contact_frames[id] = self.contact_frames[id].saturating_add(1);
+                let jumped = x.abs_diff(last.x) > self.config.max_jump
+                    || y.abs_diff(last.y) > self.config.max_jump;
+                jumped.then_some(GhostEvent::Jump { id })
+            }
+            TouchStatus::Release => {
+                let frames = core::mem::take(&mut self.contact_frames[id]);
+                let short =
+                    last.status != TouchStatus::Release && frames < self.config.min_contact_frames;
+                short.then_some(GhostEvent::ShortContact { id })
+            }
+        }
+    }
+}
+
+impl Default for NoiseMonitor {
+    fn default() -> Self {
+        Self::new(NoiseMonitorConfig::default())
+    }
+}
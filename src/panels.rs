@@ -0,0 +1,94 @@
+use super::bisync;
+use super::{CurrentFt6336uDriverInterface, Ft6336u};
+use crate::desktop::{DesktopFrame, DesktopPoint, PanelError, PanelTransform};
+use crate::{Ft6336uState, RawFrame, TouchData};
+use core::borrow::BorrowMut;
+
+/// Several controllers merged into one virtual desktop.
+///
+/// Owns `N` drivers of the same type, e.g. on separate buses of one HAL or
+/// behind an address translator, and maps their contacts with one
+/// [`PanelTransform`] each. See [`crate::desktop`].
+pub struct Panels<I2CImpl, I2CBusErr, State, const N: usize>
+where
+    I2CImpl: CurrentFt6336uDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState>,
+{
+    panels: [Ft6336u<I2CImpl, I2CBusErr, State>; N],
+    transforms: [PanelTransform; N],
+}
+
+impl<I2CImpl, I2CBusErr, State, const N: usize> Panels<I2CImpl, I2CBusErr, State, N>
+where
+    I2CImpl: CurrentFt6336uDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState>,
+{
+    pub fn new(
+        panels: [Ft6336u<I2CImpl, I2CBusErr, State>; N],
+        transforms: [PanelTransform; N],
+    ) -> Self {
+        Self { panels, transforms }
+    }
+
+    /// One driver, e.g. for configuration.
+    pub fn panel(&mut self, index: usize) -> Option<&mut Ft6336u<I2CImpl, I2CBusErr, State>> {
+        self.panels.get_mut(index)
+    }
+
+    pub fn transforms(&self) -> &[PanelTransform; N] {
+        &self.transforms
+    }
+
+    pub fn set_transform(&mut self, index: usize, transform: PanelTransform) {
+        self.transforms[index] = transform;
+    }
+
+    pub fn into_inner(self) -> [Ft6336u<I2CImpl, I2CBusErr, State>; N] {
+        self.panels
+    }
+
+    /// Scan every panel.
+    ///
+    /// All frames are read before any is decoded, so a failed or cancelled
+    /// scan leaves every panel's tracking state untouched. One failing panel
+    /// therefore fails the whole scan and the other panels' frames are
+    /// dropped; to keep the rest working around a dead panel, call
+    /// [`scan_panel`](Self::scan_panel) for each panel instead.
+    #[bisync]
+    pub async fn scan(&mut self) -> Result<DesktopFrame<N>, PanelError<I2CBusErr>> {
+        let mut frames = [RawFrame::default(); N];
+        for (panel, (driver, frame)) in self.panels.iter_mut().zip(&mut frames).enumerate() {
+            *frame = driver
+                .read_raw_frame()
+                .await
+                .map_err(|error| PanelError { panel, error })?;
+        }
+        let mut data = [TouchData::default(); N];
+        for ((driver, frame), data) in self.panels.iter_mut().zip(&frames).zip(&mut data) {
+            *data = driver.decode(frame);
+        }
+        Ok(DesktopFrame::from_panels(&data, &self.transforms))
+    }
+
+    /// Scan a single panel, e.g. the one whose INT line fired.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of range.
+    #[bisync]
+    pub async fn scan_panel(
+        &mut self,
+        index: usize,
+    ) -> Result<[DesktopPoint; 2], PanelError<I2CBusErr>> {
+        let data = self.panels[index]
+            .scan()
+            .await
+            .map_err(|error| PanelError {
+                panel: index,
+                error,
+            })?;
+        Ok(self.transforms[index].map(index, &data))
+    }
+}
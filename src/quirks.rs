@@ -0,0 +1,122 @@
+//! Per-firmware workarounds.
+//!
+//! FT6336U modules ship with varying firmware, and some revisions misbehave:
+//! inverted axes, a reduced coordinate range, or configuration registers that
+//! are silently ignored. A [`QuirkEntry`] table maps chip/firmware identity to
+//! [`Quirks`], which `init()` looks up and stores in [`crate::Ft6336uState`],
+//! so the workarounds apply to every scan without application code knowing
+//! about them.
+
+/// Identity registers read by `init()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct FirmwareIdentity {
+    pub chip_id: u8,
+    pub firmware_id: u8,
+    pub library_version: u16,
+    pub release_code_id: u8,
+}
+
+/// Workarounds for one firmware revision.
+///
+/// A reduced coordinate range is only clamped to `max_x`/`max_y`, not scaled
+/// up to the panel resolution: firmware that tops out at half the panel
+/// reports touches in half of it. Rescale in application code if needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Quirks {
+    /// Report `max_x - x` instead of `x`.
+    pub flip_x: bool,
+    /// Report `max_y - y` instead of `y`.
+    pub flip_y: bool,
+    /// Largest X coordinate the firmware reports; larger values are clamped.
+    pub max_x: u16,
+    /// Largest Y coordinate the firmware reports; larger values are clamped.
+    pub max_y: u16,
+    /// Addresses of registers the firmware ignores. Writing them through the
+    /// high-level API returns [`crate::Ft6336uError::NotSupported`].
+    pub unsupported_registers: &'static [u8],
+    /// Set for firmware that can't be used at all; `init()` fails with
+    /// [`crate::Ft6336uError::NotSupported`] and this reason.
+    pub unusable: Option<&'static str>,
+}
+
+impl Quirks {
+    /// No workarounds: full 12-bit range, all registers supported.
+    pub const NONE: Self = Self {
+        flip_x: false,
+        flip_y: false,
+        max_x: 0x0FFF,
+        max_y: 0x0FFF,
+        unsupported_registers: &[],
+        unusable: None,
+    };
+
+    pub fn is_supported(&self, address: u8) -> bool {
+        !self.unsupported_registers.contains(&address)
+    }
+
+    /// Map reported coordinates to corrected ones.
+    pub fn correct(&self, x: u16, y: u16) -> (u16, u16) {
+        (self.correct_x(x), self.correct_y(y))
+    }
+
+    /// Corrected X coordinate; the axes are corrected independently.
+    pub fn correct_x(&self, x: u16) -> u16 {
+        let x = x.min(self.max_x);
+        if self.flip_x { self.max_x - x } else { x }
+    }
+
+    /// Corrected Y coordinate; the axes are corrected independently.
+    pub fn correct_y(&self, y: u16) -> u16 {
+        let y = y.min(self.max_y);
+        if self.flip_y { self.max_y - y } else { y }
+    }
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// One row of a quirks table. `None` fields match any value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QuirkEntry {
+    pub chip_id: Option<u8>,
+    /// Inclusive `FirmwareId` range.
+    pub firmware_ids: (u8, u8),
+    pub library_version: Option<u16>,
+    pub release_code_id: Option<u8>,
+    pub quirks: Quirks,
+}
+
+impl QuirkEntry {
+    pub fn matches(&self, id: &FirmwareIdentity) -> bool {
+        self.chip_id.is_none_or(|c| c == id.chip_id)
+            && (self.firmware_ids.0..=self.firmware_ids.1).contains(&id.firmware_id)
+            && self.library_version.is_none_or(|v| v == id.library_version)
+            && self.release_code_id.is_none_or(|r| r == id.release_code_id)
+    }
+}
+
+/// Built-in table used by `init()`.
+///
+/// Intentionally empty: no revision has been confirmed to misbehave across
+/// module vendors yet, and only such firmware belongs here. Workarounds for
+/// a specific product batch can be passed to `init_with_quirks()` instead.
+pub static KNOWN_QUIRKS: &[QuirkEntry] = &[];
+
+/// Quirks of the first matching entry, or [`Quirks::NONE`].
+pub fn lookup(table: &[QuirkEntry], id: &FirmwareIdentity) -> Quirks {
+    table
+        .iter()
+        .find(|entry| entry.matches(id))
+        .map_or(Quirks::NONE, |entry| entry.quirks)
+}
@@ -0,0 +1,140 @@
+//! Frame freshness and report-rate measurement.
+//!
+//! In Monitor mode, or when the controller is wedged, `scan()` keeps returning
+//! the last report. [`RateMonitor`] compares consecutive bursts (and, if the
+//! caller passes them in, INT edges) to tell fresh reports from stale ones,
+//! measures the effective report rate and compares it with the configured
+//! `ActiveModeRate`/`MonitorModeRate`.
+//!
+//! A finger held perfectly still can produce identical bursts too. Counting
+//! INT edges makes freshness detection reliable; without them, stale streaks
+//! are only a hint. The measured rate can't exceed how often the host scans,
+//! so scan on INT or faster than the configured rate.
+
+use crate::RawFrame;
+
+/// Length of the rate measurement window.
+const RATE_WINDOW_US: u64 = 1_000_000;
+
+/// The totals wrap around like [`RawFrame::sequence`]; the streak saturates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct RateStats {
+    /// Bursts observed.
+    pub scans: u32,
+    /// Bursts that carried a new report.
+    pub fresh: u32,
+    /// INT edges reported via [`RateMonitor::interrupt`].
+    pub interrupts: u32,
+    /// Consecutive stale bursts while a contact was down.
+    pub stale_streak: u32,
+    /// Fresh reports per second over the last complete window.
+    pub measured_hz: u16,
+    /// Configured rate for the current state: `ActiveModeRate` while touched,
+    /// `MonitorModeRate` otherwise. 0 if not configured.
+    pub expected_hz: u8,
+    /// Timestamp of the last fresh report.
+    pub last_fresh_us: u64,
+}
+
+impl RateStats {
+    /// The measured rate is below `percent` of the expected rate.
+    pub fn is_slow(&self, percent: u8) -> bool {
+        self.expected_hz != 0
+            && u32::from(self.measured_hz) * 100 < u32::from(self.expected_hz) * u32::from(percent)
+    }
+
+    /// No fresh report for `timeout_us` while a contact was down, e.g. a hung
+    /// controller that needs a reset.
+    pub fn is_stalled(&self, now_us: u64, timeout_us: u64) -> bool {
+        self.stale_streak > 0 && now_us.saturating_sub(self.last_fresh_us) > timeout_us
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RateMonitor {
+    stats: RateStats,
+    active_hz: u8,
+    monitor_hz: u8,
+    last: Option<[u8; 14]>,
+    pending_interrupts: u32,
+    window_start_us: Option<u64>,
+    window_fresh: u32,
+}
+
+impl RateMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configured rates, e.g. from `read_active_rate()` and
+    /// `read_monitor_rate()`.
+    pub fn set_expected_rates(&mut self, active_hz: u8, monitor_hz: u8) {
+        self.active_hz = active_hz;
+        self.monitor_hz = monitor_hz;
+    }
+
+    /// Record an INT falling edge. The next observed burst counts as fresh.
+    pub fn interrupt(&mut self) {
+        self.stats.interrupts = self.stats.interrupts.wrapping_add(1);
+        self.pending_interrupts = self.pending_interrupts.saturating_add(1);
+    }
+
+    /// Observe a burst read at `timestamp_us`. Returns whether it carried a
+    /// new report.
+    pub fn observe(&mut self, frame: &RawFrame, timestamp_us: u64) -> bool {
+        let stats = &mut self.stats;
+        stats.scans = stats.scans.wrapping_add(1);
+        // Compare the registers only; every read gets a new sequence number.
+        let changed = self.last != Some(frame.bytes);
+        let fresh = changed || self.pending_interrupts > 0;
+        self.pending_interrupts = 0;
+        self.last = Some(frame.bytes);
+
+        // TdStatus touch count, see `RawFrame`
+        let touched = frame.bytes[1] & 0x0F != 0;
+        stats.expected_hz = if touched {
+            self.active_hz
+        } else {
+            self.monitor_hz
+        };
+
+        if fresh {
+            stats.fresh = stats.fresh.wrapping_add(1);
+            stats.stale_streak = 0;
+            stats.last_fresh_us = timestamp_us;
+            self.window_fresh = self.window_fresh.saturating_add(1);
+        } else if touched {
+            stats.stale_streak = stats.stale_streak.saturating_add(1);
+        }
+
+        let window_start = *self.window_start_us.get_or_insert(timestamp_us);
+        let elapsed = timestamp_us.saturating_sub(window_start);
+        if elapsed >= RATE_WINDOW_US {
+            let hz = u64::from(self.window_fresh) * 1_000_000 / elapsed;
+            stats.measured_hz = u16::try_from(hz).unwrap_or(u16::MAX);
+            self.window_start_us = Some(timestamp_us);
+            self.window_fresh = 0;
+        }
+        fresh
+    }
+
+    pub fn stats(&self) -> &RateStats {
+        &self.stats
+    }
+
+    /// Clear all counters, keeping the expected rates.
+    pub fn reset(&mut self) {
+        *self = Self {
+            active_hz: self.active_hz,
+            monitor_hz: self.monitor_hz,
+            ..Self::default()
+        };
+    }
+}
@@ -0,0 +1,177 @@
+//! Hit-testing region dispatcher for simple UIs.
+//!
+//! [`RegionMap`] holds up to `N` regions (rectangles or circles) with an ID
+//! and a z-order, and turns successive `scan()` results into
+//! [`RegionEvent`]s. A contact is captured by the topmost region it touches
+//! down in: it keeps reporting to that region, even when dragged outside,
+//! until it lifts.
+//!
+//! ```rust,ignore
+//! let mut ui: RegionMap<8> = RegionMap::new();
+//! ui.add(BUTTON_OK, Rect::new(20, 200, 120, 48), 0)?;
+//! ui.add(KNOB, Circle::new(240, 120, 60), 1)?;
+//!
+//! for event in ui.process(&touch.scan()?) {
+//!     if let RegionEvent::Released { region: BUTTON_OK, inside: true, .. } = event {
+//!         // clicked
+//!     }
+//! }
+//! ```
+
+use heapless::Vec;
+
+use crate::geometry::Shape;
+use crate::{TouchData, TouchStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct Region {
+    pub id: u16,
+    pub shape: Shape,
+    /// Higher values are on top.
+    pub z: i16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub enum RegionEvent {
+    /// Contact `id` touched down in `region`, which captures it.
+    Pressed {
+        region: u16,
+        id: usize,
+        x: u16,
+        y: u16,
+    },
+    /// The captured contact moved. `inside` tells whether it is still within
+    /// the region.
+    Dragged {
+        region: u16,
+        id: usize,
+        x: u16,
+        y: u16,
+        inside: bool,
+    },
+    /// The captured contact lifted at its last position. A release with
+    /// `inside == false` usually means the press was cancelled.
+    Released {
+        region: u16,
+        id: usize,
+        x: u16,
+        y: u16,
+        inside: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegionMap<const N: usize> {
+    regions: Vec<Region, N>,
+    captured: [Option<u16>; 2],
+    last: [(u16, u16); 2],
+}
+
+impl<const N: usize> RegionMap<N> {
+    pub const fn new() -> Self {
+        Self {
+            regions: Vec::new(),
+            captured: [None; 2],
+            last: [(0, 0); 2],
+        }
+    }
+
+    /// Add a region. Returns it back if the map is full.
+    pub fn add(&mut self, id: u16, shape: impl Into<Shape>, z: i16) -> Result<(), Region> {
+        self.regions.push(Region {
+            id,
+            shape: shape.into(),
+            z,
+        })
+    }
+
+    /// Remove all regions with `id`. Contacts they captured stop producing
+    /// events.
+    pub fn remove(&mut self, id: u16) {
+        self.regions.retain(|region| region.id != id);
+        for captured in &mut self.captured {
+            if *captured == Some(id) {
+                *captured = None;
+            }
+        }
+    }
+
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// Topmost region containing a point. Among equal z, the one added last
+    /// wins.
+    pub fn hit(&self, x: u16, y: u16) -> Option<&Region> {
+        self.regions
+            .iter()
+            .filter(|region| region.shape.contains(x, y))
+            .max_by_key(|region| region.z)
+    }
+
+    fn find(&self, id: u16) -> Option<&Region> {
+        self.regions.iter().find(|region| region.id == id)
+    }
+
+    /// Feed one `scan()` result.
+    pub fn process(&mut self, data: &TouchData) -> Vec<RegionEvent, 2> {
+        let mut events = Vec::new();
+        for (id, point) in data.points.iter().enumerate() {
+            let (x, y) = (point.x, point.y);
+            let event = match point.status {
+                TouchStatus::Touch => {
+                    self.captured[id] = self.hit(x, y).map(|region| region.id);
+                    self.captured[id].map(|region| RegionEvent::Pressed { region, id, x, y })
+                }
+                TouchStatus::Stream => match self.captured[id] {
+                    Some(region) if self.last[id] != (x, y) => {
+                        let inside = self.find(region).is_some_and(|r| r.shape.contains(x, y));
+                        Some(RegionEvent::Dragged {
+                            region,
+                            id,
+                            x,
+                            y,
+                            inside,
+                        })
+                    }
+                    _ => None,
+                },
+                TouchStatus::Release => self.captured[id].take().map(|region| {
+                    let inside = self.find(region).is_some_and(|r| r.shape.contains(x, y));
+                    RegionEvent::Released {
+                        region,
+                        id,
+                        x,
+                        y,
+                        inside,
+                    }
+                }),
+            };
+            self.last[id] = (x, y);
+            if let Some(event) = event {
+                // At most one event per contact.
+                let _ = events.push(event);
+            }
+        }
+        events
+    }
+}
+
+impl<const N: usize> Default for RegionMap<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,125 @@
+//! Register map metadata, generated from `device.yaml` at build time.
+//!
+//! Lets generic tools (debug shells, the CLI) list, read and decode any
+//! register by name without hand-maintained tables:
+//!
+//! ```rust,ignore
+//! let info = registers::find_by_name("PowerMode").unwrap();
+//! let mut buf = [0u8; 2];
+//! touch.read_raw(info.address, &mut buf[..info.size_bytes()])?;
+//! for field in info.decode(&buf[..info.size_bytes()]) {
+//!     // field.field.name, field.value, field.variant
+//! }
+//! ```
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Access {
+    ReadOnly,
+    ReadWrite,
+    WriteOnly,
+}
+
+impl Access {
+    pub fn is_readable(self) -> bool {
+        self != Access::WriteOnly
+    }
+
+    pub fn is_writable(self) -> bool {
+        self != Access::ReadOnly
+    }
+}
+
+#[derive(Debug)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    /// First bit of the field (LSB0).
+    pub start: u32,
+    /// One past the last bit of the field.
+    pub end: u32,
+    pub description: &'static str,
+    /// Named values of the field's conversion enum, if it has one.
+    pub variants: &'static [(&'static str, u32)],
+}
+
+impl FieldInfo {
+    pub fn extract(&self, register_value: u32) -> u32 {
+        let bits = self.end - self.start;
+        let mask = if bits >= 32 {
+            u32::MAX
+        } else {
+            (1 << bits) - 1
+        };
+        (register_value >> self.start) & mask
+    }
+
+    pub fn variant_name(&self, value: u32) -> Option<&'static str> {
+        self.variants
+            .iter()
+            .find(|&&(_, v)| v == value)
+            .map(|&(name, _)| name)
+    }
+}
+
+#[derive(Debug)]
+pub struct RegisterInfo {
+    /// Register name; block members are prefixed, e.g. `Tp0.XEvent`.
+    pub name: &'static str,
+    pub address: u8,
+    pub size_bits: u32,
+    pub access: Access,
+    pub description: &'static str,
+    pub fields: &'static [FieldInfo],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DecodedField<'a> {
+    pub field: &'a FieldInfo,
+    pub value: u32,
+    /// Conversion enum variant matching `value`, if any.
+    pub variant: Option<&'static str>,
+}
+
+impl RegisterInfo {
+    pub fn size_bytes(&self) -> usize {
+        self.size_bits.div_ceil(8) as usize
+    }
+
+    /// Combine raw bytes as read from the device (big-endian).
+    pub fn value(&self, bytes: &[u8]) -> u32 {
+        bytes
+            .iter()
+            .take(self.size_bytes())
+            .fold(0, |acc, &b| (acc << 8) | u32::from(b))
+    }
+
+    pub fn decode<'a>(&'a self, bytes: &[u8]) -> impl Iterator<Item = DecodedField<'a>> + use<'a> {
+        let value = self.value(bytes);
+        self.fields.iter().map(move |field| {
+            let field_value = field.extract(value);
+            DecodedField {
+                field,
+                value: field_value,
+                variant: field.variant_name(field_value),
+            }
+        })
+    }
+}
+
+pub static REGISTERS: &[RegisterInfo] = include!(concat!(env!("OUT_DIR"), "/registers.rs"));
+
+pub fn find(address: u8) -> Option<&'static RegisterInfo> {
+    REGISTERS.iter().find(|r| r.address == address)
+}
+
+pub fn find_by_name(name: &str) -> Option<&'static RegisterInfo> {
+    REGISTERS.iter().find(|r| r.name.eq_ignore_ascii_case(name))
+}
+
+/// Decode raw bytes read from `address` into named fields.
+pub fn decode(
+    address: u8,
+    bytes: &[u8],
+) -> Option<impl Iterator<Item = DecodedField<'static>> + use<>> {
+    find(address).map(|info| info.decode(bytes))
+}
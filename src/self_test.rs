@@ -0,0 +1,113 @@
+use super::bisync;
+use super::factory::check_channel_count;
+use super::{CurrentFt6336uDriverInterface, DelayNs, FactoryMode, Ft6336u};
+use crate::health::{HealthReport, SelfTestLimits};
+use crate::{FACTORY_MAX_CHANNELS, Ft6336uError, Ft6336uState};
+use core::borrow::BorrowMut;
+
+impl<I2CImpl, I2CBusErr, State> Ft6336u<I2CImpl, I2CBusErr, State>
+where
+    I2CImpl: CurrentFt6336uDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState>,
+{
+    /// Run the panel self-test.
+    ///
+    /// A controller that does not answer is reported as `reachable: false`
+    /// rather than as an error. Any bus error on the first read counts, not
+    /// only a NACK, so an arbitration loss or a stuck bus reads as
+    /// unreachable too; errors after that are returned.
+    ///
+    /// The channel checks run in factory mode, which is left again before
+    /// returning, so touch reporting pauses for the duration of the test. The
+    /// async test is not cancel-safe: dropping it may leave the chip in
+    /// factory mode (see [`FactoryMode`]). Panels with more than
+    /// [`FACTORY_MAX_CHANNELS`] channels fail with
+    /// [`Ft6336uError::NotSupported`].
+    #[bisync]
+    pub async fn self_test(
+        &mut self,
+        limits: &SelfTestLimits,
+        delay: &mut impl DelayNs,
+    ) -> Result<HealthReport, Ft6336uError<I2CBusErr>> {
+        let mut report = HealthReport::default();
+        match self.read_chip_id().await {
+            Ok(chip_id) => report.chip_id = chip_id,
+            Err(Ft6336uError::I2c(_)) => {
+                warn!("Self-test: controller not reachable");
+                return Ok(report);
+            }
+            Err(e) => return Err(e),
+        }
+        report.reachable = true;
+        report.firmware_id = self.read_firmware_id().await?;
+        report.library_version = self.read_library_version().await?;
+        report.firmware_ok = report.firmware_id >= limits.min_firmware_id;
+
+        let mut factory = self.enter_factory_mode(delay).await?;
+        let result = factory.check_channels(limits, delay, &mut report).await;
+        let exit = factory.exit().await;
+        result?;
+        exit?;
+
+        debug!("Self-test: {:?}", report);
+        Ok(report)
+    }
+}
+
+impl<I2CImpl, I2CBusErr, State> FactoryMode<'_, I2CImpl, I2CBusErr, State>
+where
+    I2CImpl: CurrentFt6336uDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState>,
+{
+    #[bisync]
+    async fn check_channels(
+        &mut self,
+        limits: &SelfTestLimits,
+        delay: &mut impl DelayNs,
+        report: &mut HealthReport,
+    ) -> Result<(), Ft6336uError<I2CBusErr>> {
+        report.channel_count = self.channel_count().await?;
+        // Untested channels could hide open or shorted lines; refuse rather
+        // than pass a partial test.
+        check_channel_count(report.channel_count)?;
+        let channels = report.channel_count;
+
+        let mut raw = [0u16; FACTORY_MAX_CHANNELS];
+        let raw = &mut raw[..channels];
+        self.read_raw_data(delay, raw).await?;
+        for (channel, &value) in raw.iter().enumerate() {
+            if value < limits.raw_min {
+                report.open_channels |= 1 << channel;
+            } else if value > limits.raw_max {
+                report.short_channels |= 1 << channel;
+            }
+        }
+
+        report.noise_ok = true;
+        if limits.noise_frames == 0 {
+            return Ok(());
+        }
+        let mut min = [i16::MAX; FACTORY_MAX_CHANNELS];
+        let mut max = [i16::MIN; FACTORY_MAX_CHANNELS];
+        let mut diff = [0i16; FACTORY_MAX_CHANNELS];
+        let diff = &mut diff[..channels];
+        for _ in 0..limits.noise_frames {
+            self.read_diff_data(delay, diff).await?;
+            for (channel, &value) in diff.iter().enumerate() {
+                min[channel] = min[channel].min(value);
+                max[channel] = max[channel].max(value);
+            }
+        }
+        let worst = (0..channels)
+            .map(|channel| (channel, max[channel].abs_diff(min[channel])))
+            .max_by_key(|&(_, noise)| noise);
+        if let Some((channel, noise)) = worst {
+            report.noise = Some(noise);
+            report.noisiest_channel = Some(channel);
+            report.noise_ok = noise <= limits.max_noise;
+        }
+        Ok(())
+    }
+}
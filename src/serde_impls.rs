@@ -0,0 +1,41 @@
+//! `serde` support for the enums generated from `device.yaml`, which can't
+//! carry derives. They are encoded as the value of their register field, not
+//! the whole register byte (`DeviceMode::Factory` is 4, not 0x40), as listed
+//! in `device.yaml`. Unknown values survive a round trip as `Unknown`.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    CtrlMode, DeviceMode, FactoryDataType, GestureId, GestureMode, OperatingState, PowerModeEnum,
+};
+
+macro_rules! serde_as_u8 {
+    ($($ty:ty),* $(,)?) => {$(
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_u8(u8::from(*self))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                u8::deserialize(deserializer).map(Self::from)
+            }
+        }
+
+        #[cfg(feature = "postcard")]
+        impl postcard::experimental::max_size::MaxSize for $ty {
+            const POSTCARD_MAX_SIZE: usize = 1;
+        }
+    )*};
+}
+
+serde_as_u8!(
+    CtrlMode,
+    DeviceMode,
+    FactoryDataType,
+    GestureId,
+    GestureMode,
+    OperatingState,
+    PowerModeEnum,
+);
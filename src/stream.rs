@@ -0,0 +1,122 @@
+//! `futures::Stream` of touch frames.
+//!
+//! [`touch_stream`] turns an [`Ft6336uAsync`] into a stream of changed
+//! [`TouchData`] frames, scanning whenever a [`ScanTrigger`] fires: the INT
+//! pin ([`IntTrigger`]) or a fixed poll interval ([`PollTrigger`]). It
+//! composes with stream combinators and `select!`:
+//!
+//! ```rust,ignore
+//! let touches = touch_stream(&mut touch, IntTrigger(int_pin));
+//! pin_mut!(touches);
+//! loop {
+//!     select! {
+//!         frame = touches.next() => handle(frame.unwrap()?),
+//!         key = buttons.next() => handle_key(key),
+//!     }
+//! }
+//! ```
+//!
+//! Dropping a pending `next()` future does not cancel the scan in progress:
+//! the stream keeps it and resumes it on the next poll, so no I2C transfer is
+//! abandoned half-way. Only dropping the stream itself stops it.
+
+use core::borrow::BorrowMut;
+use core::convert::Infallible;
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::I2c;
+use futures_util::stream::{self, Stream};
+use thiserror::Error;
+
+use crate::{Ft6336uAsync, Ft6336uError, Ft6336uInterface, Ft6336uState, TouchData};
+
+/// When [`touch_stream`] scans next.
+pub trait ScanTrigger {
+    type Error;
+
+    /// Wait until the next scan is due.
+    fn wait(&mut self) -> impl Future<Output = Result<(), Self::Error>>;
+}
+
+/// Scan on every falling edge of the INT pin. Put the controller in
+/// [`GestureMode::Trigger`](crate::GestureMode::Trigger) so INT pulses once
+/// per report.
+#[derive(Debug)]
+pub struct IntTrigger<P>(pub P);
+
+impl<P: Wait> ScanTrigger for IntTrigger<P> {
+    type Error = P::Error;
+
+    async fn wait(&mut self) -> Result<(), P::Error> {
+        self.0.wait_for_falling_edge().await
+    }
+}
+
+/// Scan every `interval_us`, for boards without the INT line.
+#[derive(Debug)]
+pub struct PollTrigger<D> {
+    delay: D,
+    interval_us: u32,
+}
+
+impl<D: DelayNs> PollTrigger<D> {
+    pub fn new(delay: D, interval_us: u32) -> Self {
+        Self { delay, interval_us }
+    }
+}
+
+impl<D: DelayNs> ScanTrigger for PollTrigger<D> {
+    type Error = Infallible;
+
+    async fn wait(&mut self) -> Result<(), Infallible> {
+        self.delay.delay_us(self.interval_us).await;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TouchStreamError<I2cErr, TriggerErr> {
+    #[error("Touch controller error: {0}")]
+    Touch(Ft6336uError<I2cErr>),
+    #[error("Scan trigger error")]
+    Trigger(TriggerErr),
+}
+
+/// Stream of touch frames, scanned whenever `trigger` fires.
+///
+/// Frames identical to the last yielded one are skipped, so a finger resting
+/// still (or an idle panel, when polling) does not produce items. Errors are
+/// yielded as items; the stream never ends on its own, so stop polling it to
+/// give up.
+pub fn touch_stream<'a, I2CBus, E, State, T>(
+    touch: &'a mut Ft6336uAsync<Ft6336uInterface<I2CBus>, E, State>,
+    trigger: T,
+) -> impl Stream<Item = Result<TouchData, TouchStreamError<E, T::Error>>> + 'a
+where
+    I2CBus: I2c<Error = E> + 'a,
+    E: core::fmt::Debug + 'a,
+    State: BorrowMut<Ft6336uState> + 'a,
+    T: ScanTrigger + 'a,
+{
+    stream::unfold(
+        (touch, trigger, TouchData::default()),
+        |(touch, mut trigger, mut last)| async move {
+            let item = loop {
+                if let Err(e) = trigger.wait().await {
+                    break Err(TouchStreamError::Trigger(e));
+                }
+                match touch.scan().await {
+                    Ok(data) if data == last => continue,
+                    Ok(data) => {
+                        last = data;
+                        break Ok(data);
+                    }
+                    Err(e) => break Err(TouchStreamError::Touch(e)),
+                }
+            };
+            Some((item, (touch, trigger, last)))
+        },
+    )
+}
@@ -0,0 +1,229 @@
+//! Automatic touch threshold tuning.
+//!
+//! The right `Threshold` (0x80) depends on how strong a touch looks to the
+//! controller, which changes with gloves, moisture and cover-glass thickness.
+//! [`ThresholdTuner`] watches the `weight` and `area` of reported contacts and
+//! the rate of false (very short) touches over a window of frames, and moves
+//! the threshold towards a fraction of the typical contact weight, within
+//! configured bounds and with hysteresis. `scan_with_tuner()` applies its
+//! decisions.
+
+use crate::{TouchData, TouchStatus};
+
+/// Ready-made tuning profiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub enum TuningPreset {
+    /// Bare fingers.
+    #[default]
+    Normal,
+    /// Gloved fingers: weak signal, so allow low thresholds.
+    Glove,
+    /// Wet fingers or droplets on the glass: favour rejecting false touches.
+    WetFinger,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "postcard",
+    derive(postcard::experimental::max_size::MaxSize)
+)]
+pub struct TunerConfig {
+    /// Threshold bounds. `ThresholdTuner` swaps them if `min_threshold` is
+    /// larger, e.g. in a hand-edited or deserialized config.
+    pub min_threshold: u8,
+    pub max_threshold: u8,
+    /// Target threshold as a percentage of the average contact weight.
+    pub weight_ratio_percent: u8,
+    /// Differences from the target up to this are left alone.
+    pub hysteresis: u8,
+    /// Largest change applied per window.
+    pub step: u8,
+    /// Contacts with a larger area (e.g. water films, palms) are not used for
+    /// the weight average.
+    pub max_area: u8,
+    /// More false touches per window than this raise the threshold.
+    pub max_false_touches: u8,
+    /// Window length in frames that have at least one contact.
+    pub window_frames: u16,
+}
+
+impl TunerConfig {
+    pub fn preset(preset: TuningPreset) -> Self {
+        match preset {
+            TuningPreset::Normal => Self {
+                min_threshold: 15,
+                max_threshold: 60,
+                weight_ratio_percent: 50,
+                hysteresis: 4,
+                step: 4,
+                max_area: 10,
+                max_false_touches: 2,
+                window_frames: 120,
+            },
+            TuningPreset::Glove => Self {
+                min_threshold: 8,
+                max_threshold: 40,
+                weight_ratio_percent: 35,
+                hysteresis: 3,
+                step: 3,
+                max_area: 12,
+                max_false_touches: 2,
+                window_frames: 120,
+            },
+            TuningPreset::WetFinger => Self {
+                min_threshold: 25,
+                max_threshold: 90,
+                weight_ratio_percent: 60,
+                hysteresis: 4,
+                step: 6,
+                max_area: 6,
+                max_false_touches: 0,
+                window_frames: 60,
+            },
+        }
+    }
+
+    /// This config with `min_threshold <= max_threshold`.
+    pub fn normalized(self) -> Self {
+        Self {
+            min_threshold: self.min_threshold.min(self.max_threshold),
+            max_threshold: self.min_threshold.max(self.max_threshold),
+            ..self
+        }
+    }
+}
+
+impl Default for TunerConfig {
+    fn default() -> Self {
+        Self::preset(TuningPreset::Normal)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ThresholdTuner {
+    config: TunerConfig,
+    threshold: u8,
+    weight_sum: u32,
+    samples: u32,
+    frames: u16,
+    false_touches: u8,
+    contact_frames: [u8; 2],
+    last_status: [TouchStatus; 2],
+}
+
+impl ThresholdTuner {
+    /// `threshold` is the value currently programmed, e.g. from
+    /// `read_touch_threshold()`.
+    pub fn new(config: TunerConfig, threshold: u8) -> Self {
+        let config = config.normalized();
+        Self {
+            config,
+            threshold: threshold.clamp(config.min_threshold, config.max_threshold),
+            weight_sum: 0,
+            samples: 0,
+            frames: 0,
+            false_touches: 0,
+            contact_frames: [0; 2],
+            last_status: [TouchStatus::Release; 2],
+        }
+    }
+
+    pub fn config(&self) -> &TunerConfig {
+        &self.config
+    }
+
+    /// Switch profile, e.g. when the user enables glove mode. The current
+    /// window is discarded.
+    pub fn set_config(&mut self, config: TunerConfig) {
+        *self = Self::new(config, self.threshold);
+    }
+
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Feed one `scan()` result. Returns the new threshold when it should be
+    /// written to the controller.
+    pub fn observe(&mut self, data: &TouchData) -> Option<u8> {
+        let mut any_contact = false;
+        for (id, point) in data.points.iter().enumerate() {
+            match point.status {
+                TouchStatus::Touch | TouchStatus::Stream => {
+                    any_contact = true;
+                    self.contact_frames[id] = self.contact_frames[id].saturating_add(1);
+                    if point.area <= self.config.max_area {
+                        self.weight_sum += u32::from(point.weight);
+                        self.samples += 1;
+                    }
+                }
+                TouchStatus::Release => {
+                    if self.last_status[id] != TouchStatus::Release && self.contact_frames[id] < 2 {
+                        self.false_touches = self.false_touches.saturating_add(1);
+                    }
+                    self.contact_frames[id] = 0;
+                }
+            }
+            self.last_status[id] = point.status;
+        }
+
+        if !any_contact {
+            return None;
+        }
+        self.frames += 1;
+        if self.frames < self.config.window_frames {
+            return None;
+        }
+        let adjusted = self.adjust();
+        self.weight_sum = 0;
+        self.samples = 0;
+        self.frames = 0;
+        self.false_touches = 0;
+        adjusted
+    }
+
+    fn adjust(&mut self) -> Option<u8> {
+        let config = &self.config;
+        let current = self.threshold;
+        if self.false_touches > config.max_false_touches {
+            // False touches override the weight target and the hysteresis.
+            let new = current
+                .saturating_add(config.step)
+                .min(config.max_threshold);
+            debug!(
+                "Threshold {} -> {} ({} false touches)",
+                current, new, self.false_touches
+            );
+            self.threshold = new;
+            return (new != current).then_some(new);
+        }
+        if self.samples == 0 {
+            return None;
+        }
+        let average = self.weight_sum / self.samples;
+        let target = average * u32::from(config.weight_ratio_percent) / 100;
+        let target = u8::try_from(target)
+            .unwrap_or(u8::MAX)
+            .clamp(config.min_threshold, config.max_threshold);
+
+        if target.abs_diff(current) <= config.hysteresis {
+            return None;
+        }
+        let new = if target > current {
+            current.saturating_add(config.step).min(target)
+        } else {
+            current.saturating_sub(config.step).max(target)
+        };
+        debug!("Threshold {} -> {} (target {})", current, new, target);
+        self.threshold = new;
+        (new != current).then_some(new)
+    }
+}
@@ -0,0 +1,144 @@
+use super::bisync;
+use super::{DelayNs, Ft6336u, I2c};
+use crate::firmware::{
+    BootloaderConfig, UPGRADE_PACKET_SIZE, UpgradeError, UpgradeProgress, checksum,
+};
+use crate::{FT6336U_I2C_ADDRESS, Ft6336uError, Ft6336uInterface, Ft6336uState};
+use core::borrow::BorrowMut;
+
+// Bootloader commands
+const CMD_UPGRADE: [u8; 2] = [0x55, 0xAA];
+const CMD_READ_ID: [u8; 4] = [0x90, 0x00, 0x00, 0x00];
+const CMD_ERASE_APP: u8 = 0x61;
+const CMD_WRITE: u8 = 0xBF;
+const CMD_CHECKSUM: u8 = 0xCC;
+const CMD_RESET: u8 = 0x07;
+
+// Values written to the `State` register (0xBC) to reset into the bootloader
+const RESET_AA: u8 = 0xAA;
+const RESET_55: u8 = 0x55;
+
+// Largest address the 3-byte packet header can carry
+const MAX_IMAGE_SIZE: usize = 1 << 24;
+
+impl<I2CBus, E, State> Ft6336u<Ft6336uInterface<I2CBus>, E, State>
+where
+    I2CBus: I2c<Error = E>,
+    E: core::fmt::Debug,
+    State: BorrowMut<Ft6336uState>,
+{
+    /// Flash a new application image through the FocalTech I2C bootloader.
+    ///
+    /// Resets the chip into the bootloader, checks its ID, erases the
+    /// application, writes `image` in [`UPGRADE_PACKET_SIZE`] packets,
+    /// verifies the XOR checksum and resets back into the new firmware.
+    /// `progress` is called at the start of every stage and after each packet.
+    ///
+    /// On a boot ID mismatch the chip is reset before returning. After any
+    /// other error it stays in the bootloader, and the upgrade can simply be
+    /// retried.
+    ///
+    /// The async upgrade is not cancel-safe. Dropping it leaves the chip in
+    /// whatever stage it reached, from still running the old application to
+    /// a partly written image; reset the chip with its RST line and run the
+    /// upgrade again.
+    #[bisync]
+    pub async fn upgrade_firmware(
+        &mut self,
+        image: &[u8],
+        config: &BootloaderConfig,
+        delay: &mut impl DelayNs,
+        mut progress: impl FnMut(UpgradeProgress),
+    ) -> Result<(), Ft6336uError<E>> {
+        if image.is_empty() {
+            return Err(UpgradeError::EmptyImage.into());
+        }
+        if image.len() > MAX_IMAGE_SIZE {
+            return Err(UpgradeError::ImageTooLarge.into());
+        }
+
+        progress(UpgradeProgress::EnteringBootloader);
+        self.write_state(RESET_AA).await?;
+        delay.delay_ms(10).await;
+        // The chip resets on this write and may not acknowledge it.
+        let _ = self.write_state(RESET_55).await;
+        delay.delay_ms(config.reset_delay_ms).await;
+        self.boot_write(&CMD_UPGRADE).await?;
+        delay.delay_ms(10).await;
+
+        let mut boot_id = [0u8; 2];
+        self.boot_write_read(&CMD_READ_ID, &mut boot_id).await?;
+        if boot_id != config.boot_id {
+            warn!("Unexpected boot ID {:?}", boot_id);
+            self.boot_write(&[CMD_RESET]).await?;
+            delay.delay_ms(config.boot_delay_ms).await;
+            return Err(UpgradeError::BootIdMismatch {
+                expected: config.boot_id,
+                found: boot_id,
+            }
+            .into());
+        }
+
+        progress(UpgradeProgress::Erasing);
+        self.boot_write(&[CMD_ERASE_APP]).await?;
+        delay.delay_ms(config.erase_delay_ms).await;
+
+        let total = image.len();
+        progress(UpgradeProgress::Writing { written: 0, total });
+        let mut packet = [0u8; 6 + UPGRADE_PACKET_SIZE];
+        for (index, chunk) in image.chunks(UPGRADE_PACKET_SIZE).enumerate() {
+            let address = index * UPGRADE_PACKET_SIZE;
+            packet[0] = CMD_WRITE;
+            packet[1] = (address >> 16) as u8;
+            packet[2] = (address >> 8) as u8;
+            packet[3] = address as u8;
+            packet[4] = (chunk.len() >> 8) as u8;
+            packet[5] = chunk.len() as u8;
+            packet[6..6 + chunk.len()].copy_from_slice(chunk);
+            self.boot_write(&packet[..6 + chunk.len()]).await?;
+            delay.delay_ms(config.packet_delay_ms).await;
+            progress(UpgradeProgress::Writing {
+                written: address + chunk.len(),
+                total,
+            });
+        }
+
+        progress(UpgradeProgress::Verifying);
+        let mut found = [0u8; 1];
+        self.boot_write_read(&[CMD_CHECKSUM], &mut found).await?;
+        let expected = checksum(image);
+        if found[0] != expected {
+            return Err(UpgradeError::ChecksumMismatch {
+                expected,
+                found: found[0],
+            }
+            .into());
+        }
+
+        progress(UpgradeProgress::Resetting);
+        self.boot_write(&[CMD_RESET]).await?;
+        delay.delay_ms(config.boot_delay_ms).await;
+        info!("Firmware upgrade complete ({} bytes)", total);
+        Ok(())
+    }
+
+    #[bisync]
+    async fn boot_write(&mut self, data: &[u8]) -> Result<(), Ft6336uError<E>> {
+        self.i2c_mut()
+            .write(FT6336U_I2C_ADDRESS, data)
+            .await
+            .map_err(Ft6336uError::I2c)
+    }
+
+    #[bisync]
+    async fn boot_write_read(
+        &mut self,
+        command: &[u8],
+        response: &mut [u8],
+    ) -> Result<(), Ft6336uError<E>> {
+        self.i2c_mut()
+            .write_read(FT6336U_I2C_ADDRESS, command, response)
+            .await
+            .map_err(Ft6336uError::I2c)
+    }
+}
@@ -0,0 +1,242 @@
+//! Cancel safety of the async scan paths.
+//!
+//! Every I2C transfer of the simulated bus suspends once before it runs, so
+//! polling a call `n` times and dropping it interrupts it at each `.await` in
+//! turn. After every interruption the host state must be untouched, and
+//! retrying must give the same result as an uninterrupted call.
+
+mod common;
+
+use core::cell::Cell;
+
+use common::{Bus, complete, poll_n};
+use embedded_hal::i2c::ErrorKind;
+use ft6336u_dd::{
+    DesktopFrame, Ft6336uAsync, Ft6336uInterface, Ft6336uState, NoiseMonitor, NoiseMonitorConfig,
+    PanelTransform, PanelsAsync, ProximityState, RawFrame, ThresholdTuner, TouchStatus,
+    TunerConfig,
+};
+
+const THRESHOLD: u8 = 0x80;
+const FREQ_HOPPING_EN: u8 = 0x8B;
+const FACE_DEC_MODE: u8 = 0xB0;
+
+/// Call `attempt(polls)` with 1, 2, ... polls until it reports completion,
+/// checking that the call was interrupted at least once on the way.
+fn interrupt_everywhere(mut attempt: impl FnMut(usize) -> bool) {
+    let mut polls = 1;
+    while !attempt(polls) {
+        polls += 1;
+    }
+    assert!(polls > 1, "call never suspended");
+}
+
+fn debug(value: &impl core::fmt::Debug) -> String {
+    format!("{value:?}")
+}
+
+/// Whether `state` decodes a proximity result from the gesture ID.
+fn decodes_proximity(mut state: Ft6336uState) -> bool {
+    let mut bytes = [0; 14];
+    bytes[0] = 0xC0;
+    state.update(&RawFrame { bytes, sequence: 0 }).proximity == Some(ProximityState::Near)
+}
+
+#[test]
+fn scan() {
+    let bus = Bus::with_contact(Some((100, 200)));
+    let base = Ft6336uState::new();
+    let reference = {
+        let (mut state, mut bus) = (base, bus.clone());
+        complete(state.attach_async(&mut bus).scan()).unwrap()
+    };
+    assert_eq!(reference.points[0].status, TouchStatus::Touch);
+
+    interrupt_everywhere(|polls| {
+        let (mut state, mut bus) = (base, bus.clone());
+        let out = poll_n(state.attach_async(&mut bus).scan(), polls);
+        if let Some(data) = out {
+            assert_eq!(data.unwrap(), reference);
+            return true;
+        }
+        assert_eq!(state.touch_data(), base.touch_data());
+        let retried = complete(state.attach_async(&mut bus).scan()).unwrap();
+        assert_eq!(retried, reference);
+        false
+    });
+}
+
+#[test]
+fn scan_with_tuner() {
+    let bus = Bus::with_contact(Some((100, 200)));
+    let base = Ft6336uState::new();
+    let config = TunerConfig {
+        window_frames: 1,
+        ..TunerConfig::default()
+    };
+    let base_tuner = ThresholdTuner::new(config, config.min_threshold);
+    let (reference, reference_tuner, reference_writes) = {
+        let (mut state, mut bus, mut tuner) = (base, bus.clone(), base_tuner);
+        let data = complete(state.attach_async(&mut bus).scan_with_tuner(&mut tuner)).unwrap();
+        (data, tuner, bus.writes)
+    };
+    // The frame must make the tuner write, or there is no write to cancel.
+    assert_eq!(reference_writes, [(THRESHOLD, reference_tuner.threshold())]);
+
+    interrupt_everywhere(|polls| {
+        let (mut state, mut bus, mut tuner) = (base, bus.clone(), base_tuner);
+        let out = poll_n(
+            state.attach_async(&mut bus).scan_with_tuner(&mut tuner),
+            polls,
+        );
+        if let Some(data) = out {
+            assert_eq!(data.unwrap(), reference);
+            return true;
+        }
+        assert_eq!(state.touch_data(), base.touch_data());
+        assert_eq!(debug(&tuner), debug(&base_tuner));
+        assert!(bus.writes.is_empty());
+
+        let data = complete(state.attach_async(&mut bus).scan_with_tuner(&mut tuner)).unwrap();
+        assert_eq!(data, reference);
+        assert_eq!(debug(&tuner), debug(&reference_tuner));
+        assert_eq!(bus.writes, reference_writes);
+        false
+    });
+}
+
+#[test]
+fn scan_with_noise_monitor() {
+    let config = NoiseMonitorConfig {
+        trigger_events: 1,
+        ..NoiseMonitorConfig::default()
+    };
+    // A contact seen for a single frame: lifting it now is a ghost touch.
+    let mut bus = Bus::with_contact(Some((100, 200)));
+    let mut base = Ft6336uState::new();
+    let mut base_monitor = NoiseMonitor::new(config);
+    complete(
+        base.attach_async(&mut bus)
+            .scan_with_noise_monitor(&mut base_monitor, || {}),
+    )
+    .unwrap();
+    bus.set_contact(None);
+    bus.writes.clear();
+
+    let switched = Cell::new(0);
+    let (reference, reference_monitor, reference_writes) = {
+        let (mut state, mut bus, mut monitor) = (base, bus.clone(), base_monitor);
+        let data = complete(
+            state
+                .attach_async(&mut bus)
+                .scan_with_noise_monitor(&mut monitor, || switched.set(switched.get() + 1)),
+        )
+        .unwrap();
+        (data, monitor, bus.writes)
+    };
+    assert_eq!(reference_writes, [(FREQ_HOPPING_EN, 1)]);
+    assert_eq!(switched.get(), 1);
+
+    interrupt_everywhere(|polls| {
+        let (mut state, mut bus, mut monitor) = (base, bus.clone(), base_monitor);
+        switched.set(0);
+        let out = poll_n(
+            state
+                .attach_async(&mut bus)
+                .scan_with_noise_monitor(&mut monitor, || switched.set(switched.get() + 1)),
+            polls,
+        );
+        if let Some(data) = out {
+            assert_eq!(data.unwrap(), reference);
+            assert_eq!(switched.get(), 1);
+            return true;
+        }
+        assert_eq!(state.touch_data(), base.touch_data());
+        assert_eq!(debug(&monitor), debug(&base_monitor));
+        assert!(bus.writes.is_empty());
+        assert_eq!(switched.get(), 0);
+
+        let data = complete(
+            state
+                .attach_async(&mut bus)
+                .scan_with_noise_monitor(&mut monitor, || switched.set(switched.get() + 1)),
+        )
+        .unwrap();
+        assert_eq!(data, reference);
+        assert_eq!(debug(&monitor), debug(&reference_monitor));
+        assert_eq!(bus.writes, reference_writes);
+        assert_eq!(switched.get(), 1);
+        false
+    });
+}
+
+type Panels = PanelsAsync<Ft6336uInterface<Bus>, ErrorKind, Ft6336uState, 2>;
+
+fn panels(parts: [(Bus, Ft6336uState); 2]) -> Panels {
+    let mut right = PanelTransform::new(320, 480);
+    right.offset_x = 320;
+    PanelsAsync::new(
+        parts.map(|(bus, state)| Ft6336uAsync::with_state(bus, state)),
+        [PanelTransform::new(320, 480), right],
+    )
+}
+
+fn into_parts(panels: Panels) -> [(Bus, Ft6336uState); 2] {
+    panels.into_inner().map(|driver| driver.into_parts())
+}
+
+#[test]
+fn panels_scan() {
+    let base = [
+        (Bus::with_contact(Some((10, 20))), Ft6336uState::new()),
+        (Bus::with_contact(Some((30, 40))), Ft6336uState::new()),
+    ];
+    let reference: DesktopFrame<2> = complete(panels(base.clone()).scan()).unwrap();
+    assert_eq!(reference.points[1][0].point.x, 350);
+
+    interrupt_everywhere(|polls| {
+        let mut set = panels(base.clone());
+        let out = poll_n(set.scan(), polls);
+        if let Some(frame) = out {
+            assert_eq!(frame.unwrap(), reference);
+            return true;
+        }
+        let parts = into_parts(set);
+        for ((_, state), (_, base_state)) in parts.iter().zip(&base) {
+            assert_eq!(state.touch_data(), base_state.touch_data());
+        }
+        let retried = complete(panels(parts).scan()).unwrap();
+        assert_eq!(retried, reference);
+        false
+    });
+}
+
+#[test]
+fn enable_proximity_decodes_before_write() {
+    let bus = Bus::new();
+    let base = Ft6336uState::new();
+    interrupt_everywhere(|polls| {
+        let (mut state, mut bus) = (base, bus.clone());
+        let done = poll_n(state.attach_async(&mut bus).enable_proximity(true), polls).is_some();
+        // Whether or not the write went out, results are decoded.
+        assert!(decodes_proximity(state));
+        assert_eq!(bus.registers[usize::from(FACE_DEC_MODE)], u8::from(done));
+        done
+    });
+}
+
+#[test]
+fn disable_proximity_decodes_until_written() {
+    let mut bus = Bus::new();
+    bus.registers[usize::from(FACE_DEC_MODE)] = 1;
+    let mut base = Ft6336uState::new();
+    base.set_proximity_enabled(true);
+    interrupt_everywhere(|polls| {
+        let (mut state, mut bus) = (base, bus.clone());
+        let done = poll_n(state.attach_async(&mut bus).enable_proximity(false), polls).is_some();
+        // Decoding stops only once the chip has been told to stop reporting.
+        assert_eq!(decodes_proximity(state), !done);
+        assert_eq!(bus.registers[usize::from(FACE_DEC_MODE)], u8::from(!done));
+        done
+    });
+}
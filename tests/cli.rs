@@ -0,0 +1,170 @@
+//! `ft6336u-cli` commands against a mocked I2C bus.
+#![cfg(feature = "cli")]
+
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use ft6336u_dd::registers::REGISTERS;
+use ft6336u_dd::{FT6336U_I2C_ADDRESS, Ft6336u, cli};
+
+const ADDR: u8 = FT6336U_I2C_ADDRESS;
+
+fn read(register: u8, response: &[u8]) -> Transaction {
+    Transaction::write_read(ADDR, vec![register], response.to_vec())
+}
+
+fn write(register: u8, value: u8) -> Transaction {
+    Transaction::write(ADDR, vec![register, value])
+}
+
+/// Run `args` against a bus expecting exactly `expectations`, returning the
+/// output.
+fn run(args: &[&str], expectations: &[Transaction]) -> String {
+    let mut mock = Mock::new(expectations);
+    let mut touch = Ft6336u::new(mock.clone());
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    let mut out = Vec::new();
+    cli::run(&mut touch, &args, &mut out).unwrap();
+    mock.done();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn info() {
+    let out = run(
+        &["info"],
+        &[
+            read(0xA3, &[0x64]),
+            read(0x9F, &[0x26]),
+            read(0xA0, &[0x01]),
+            read(0xA8, &[0x11]),
+            read(0xA6, &[0x10]),
+            read(0xA1, &[0x30, 0x03]),
+            read(0xAF, &[0x01]),
+            read(0xA5, &[0x00]),
+        ],
+    );
+    assert!(out.contains("Chip ID:         0x64"));
+    assert!(out.contains("Cipher:          0x26 0x01"));
+    assert!(out.contains("Firmware ID:     0x10"));
+    assert!(out.contains("Library version: 0x3003"));
+    assert!(out.contains("Power mode:      Active"));
+}
+
+#[test]
+fn dump() {
+    let readable: Vec<_> = REGISTERS
+        .iter()
+        .filter(|r| r.access.is_readable())
+        .collect();
+    let expectations: Vec<_> = readable
+        .iter()
+        .map(|r| read(r.address, &vec![0; r.size_bytes()]))
+        .collect();
+    let out = run(&["dump"], &expectations);
+    assert_eq!(out.lines().count(), readable.len());
+    assert!(out.lines().any(|l| l.starts_with("0x80 Threshold")));
+}
+
+#[test]
+fn get_by_name_and_address() {
+    let out = run(&["get", "Threshold"], &[read(0x80, &[0x28])]);
+    assert!(out.starts_with("0x80 Threshold"));
+    assert!(out.contains("= 0x28"));
+
+    let out = run(&["get", "0xa6"], &[read(0xA6, &[0x10])]);
+    assert!(out.starts_with("0xa6 FirmwareId"));
+}
+
+#[test]
+fn set() {
+    let out = run(&["set", "0x80", "40"], &[write(0x80, 40)]);
+    assert!(out.starts_with("0x80 Threshold"));
+    assert!(out.contains("= 0x28"));
+}
+
+#[test]
+fn set_rejects_read_only_and_oversized_values() {
+    let mut touch = Ft6336u::new(Mock::new(&[]));
+    let mut out = Vec::new();
+    for args in [["set", "ChipId", "1"], ["set", "Threshold", "256"]] {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        assert!(cli::run(&mut touch, &args, &mut out).is_err());
+    }
+    touch.release().done();
+}
+
+#[test]
+fn set_by_shared_address_picks_writable_register() {
+    // GestureId (RO) and FactoryLineNum (RW) share 0x01.
+    let out = run(&["set", "0x01", "5"], &[write(0x01, 5)]);
+    assert!(out.starts_with("0x01 FactoryLineNum"), "{out}");
+
+    // TdStatus and FactoryTxNum share 0x02, both read-only.
+    let mut touch = Ft6336u::new(Mock::new(&[]));
+    let args = ["set", "0x02", "1"].map(String::from);
+    let err = cli::run(&mut touch, &args, &mut Vec::new()).unwrap_err();
+    assert!(err.to_string().contains("read-only"), "{err}");
+    touch.release().done();
+}
+
+#[test]
+fn watch() {
+    let burst = |contact: Option<(u8, u8)>| {
+        let mut bytes = vec![0; 14];
+        if let Some((x, y)) = contact {
+            bytes[1] = 1;
+            bytes[2] = 0x80;
+            bytes[3] = x;
+            bytes[5] = y;
+        }
+        read(0x01, &bytes)
+    };
+    let out = run(
+        &["watch", "4"],
+        &[
+            burst(Some((10, 20))),
+            burst(Some((10, 20))),
+            burst(Some((30, 40))),
+            burst(None),
+        ],
+    );
+    // The repeated second frame is a Stream and printed; the third is a move.
+    assert_eq!(
+        out,
+        "0: Touch x=10 y=20\n\
+         0: Stream x=10 y=20\n\
+         0: Stream x=30 y=40\n\
+         0: Release x=30 y=40\n\
+         1: Release x=0 y=0\n"
+    );
+}
+
+#[test]
+fn rate() {
+    let out = run(
+        &["rate", "60", "25"],
+        &[
+            write(0x88, 60),
+            write(0x89, 25),
+            read(0x88, &[60]),
+            read(0x89, &[25]),
+        ],
+    );
+    assert_eq!(out, "Active rate:  60 Hz\nMonitor rate: 25 Hz\n");
+}
+
+#[test]
+fn threshold() {
+    let out = run(&["threshold"], &[read(0x80, &[22])]);
+    assert_eq!(out, "Threshold: 22\n");
+
+    let out = run(&["threshold", "40"], &[write(0x80, 40), read(0x80, &[40])]);
+    assert_eq!(out, "Threshold: 40\n");
+}
+
+#[test]
+fn unknown_command_prints_usage() {
+    let mut touch = Ft6336u::new(Mock::new(&[]));
+    let err = cli::run(&mut touch, &["frobnicate".to_string()], &mut Vec::new()).unwrap_err();
+    assert_eq!(err.to_string(), cli::USAGE);
+    touch.release().done();
+}
@@ -0,0 +1,142 @@
+//! Simulated controller for the async tests.
+
+// Each test crate uses a different subset.
+#![allow(dead_code)]
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+use std::collections::VecDeque;
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, Operation};
+use ft6336u_dd::FT6336U_I2C_ADDRESS;
+
+/// Register file behind an I2C bus whose transfers each suspend once.
+#[derive(Clone)]
+pub struct Bus {
+    pub registers: [u8; 256],
+    pub pointer: u8,
+    /// `(register, value)` of every byte written.
+    pub writes: Vec<(u8, u8)>,
+    /// Applied at the start of each transfer, one entry per transfer: a new
+    /// contact (see [`Bus::set_contact`]) or a bus fault.
+    pub script: VecDeque<Result<Option<(u16, u16)>, ErrorKind>>,
+    /// Transfers that ran to completion.
+    pub transfers: usize,
+}
+
+impl Bus {
+    pub fn new() -> Self {
+        Self {
+            registers: [0; 256],
+            pointer: 0,
+            writes: Vec::new(),
+            script: VecDeque::new(),
+            transfers: 0,
+        }
+    }
+
+    /// A bus reporting one contact at `(x, y)`, or none.
+    pub fn with_contact(contact: Option<(u16, u16)>) -> Self {
+        let mut bus = Self::new();
+        bus.set_contact(contact);
+        bus
+    }
+
+    /// A bus that plays `script`, one entry per transfer.
+    pub fn scripted(
+        script: impl IntoIterator<Item = Result<Option<(u16, u16)>, ErrorKind>>,
+    ) -> Self {
+        Self {
+            script: script.into_iter().collect(),
+            ..Self::new()
+        }
+    }
+
+    pub fn set_contact(&mut self, contact: Option<(u16, u16)>) {
+        let frame = &mut self.registers[0x01..0x0F];
+        frame.fill(0);
+        if let Some((x, y)) = contact {
+            frame[1] = 1;
+            frame[2] = 0x80 | (x >> 8) as u8;
+            frame[3] = x as u8;
+            frame[4] = (y >> 8) as u8;
+            frame[5] = y as u8;
+            frame[6] = 100; // weight
+            frame[7] = 0x30; // area
+        }
+    }
+}
+
+impl ErrorType for Bus {
+    type Error = ErrorKind;
+}
+
+impl embedded_hal_async::i2c::I2c for Bus {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        assert_eq!(address, FT6336U_I2C_ADDRESS);
+        YieldOnce(false).await;
+        match self.script.pop_front() {
+            Some(Ok(contact)) => self.set_contact(contact),
+            Some(Err(e)) => return Err(e),
+            None => {}
+        }
+        for operation in operations {
+            match operation {
+                Operation::Write(data) => {
+                    let Some((&register, values)) = data.split_first() else {
+                        continue;
+                    };
+                    self.pointer = register;
+                    for &value in values {
+                        self.registers[usize::from(self.pointer)] = value;
+                        self.writes.push((self.pointer, value));
+                        self.pointer = self.pointer.wrapping_add(1);
+                    }
+                }
+                Operation::Read(buffer) => {
+                    for byte in buffer.iter_mut() {
+                        *byte = self.registers[usize::from(self.pointer)];
+                        self.pointer = self.pointer.wrapping_add(1);
+                    }
+                }
+            }
+        }
+        self.transfers += 1;
+        Ok(())
+    }
+}
+
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: core::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Poll `future` up to `polls` times, dropping it if it is still pending.
+pub fn poll_n<F: Future>(future: F, polls: usize) -> Option<F::Output> {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    (0..polls).find_map(|_| match future.as_mut().poll(&mut cx) {
+        Poll::Ready(output) => Some(output),
+        Poll::Pending => None,
+    })
+}
+
+pub fn complete<F: Future>(future: F) -> F::Output {
+    poll_n(future, usize::MAX).unwrap()
+}
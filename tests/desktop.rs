@@ -0,0 +1,78 @@
+//! Panel transforms and desktop-wide contact IDs.
+
+use ft6336u_dd::{DesktopFrame, PanelTransform, Rotation, TouchData, TouchPoint, TouchStatus};
+
+/// A 320 x 480 panel.
+fn panel(rotation: Rotation, flip_x: bool, flip_y: bool) -> PanelTransform {
+    PanelTransform {
+        rotation,
+        flip_x,
+        flip_y,
+        ..PanelTransform::new(320, 480)
+    }
+}
+
+#[test]
+fn rotations() {
+    let cases = [
+        (Rotation::None, (10, 20), (0, 0), (319, 479)),
+        (Rotation::Cw90, (459, 10), (479, 0), (0, 319)),
+        (Rotation::Cw180, (309, 459), (319, 479), (0, 0)),
+        (Rotation::Cw270, (20, 309), (0, 319), (479, 0)),
+    ];
+    for (rotation, point, top_left, bottom_right) in cases {
+        let transform = panel(rotation, false, false);
+        assert_eq!(transform.apply(10, 20), point, "{rotation:?}");
+        // Corners stay corners of the rotated panel.
+        assert_eq!(transform.apply(0, 0), top_left, "{rotation:?}");
+        assert_eq!(transform.apply(319, 479), bottom_right, "{rotation:?}");
+    }
+}
+
+#[test]
+fn flips_apply_before_rotation() {
+    assert_eq!(panel(Rotation::None, true, false).apply(10, 20), (309, 20));
+    assert_eq!(panel(Rotation::None, false, true).apply(10, 20), (10, 459));
+    // Mirroring both axes undoes a half turn.
+    assert_eq!(panel(Rotation::Cw180, true, true).apply(10, 20), (10, 20));
+    // Flipped X becomes the rotated Y.
+    assert_eq!(panel(Rotation::Cw90, true, false).apply(10, 20), (459, 309));
+}
+
+#[test]
+fn out_of_range_points_are_clamped_and_offset() {
+    let mut transform = panel(Rotation::None, false, false);
+    assert_eq!(transform.apply(1000, 1000), (319, 479));
+
+    transform.offset_x = 320;
+    transform.offset_y = u16::MAX - 100;
+    assert_eq!(transform.apply(10, 20), (330, u16::MAX - 80));
+    assert_eq!(transform.apply(10, 200), (330, u16::MAX));
+}
+
+#[test]
+fn desktop_ids_span_panels() {
+    let mut data = TouchData::default();
+    data.points[1] = TouchPoint {
+        status: TouchStatus::Touch,
+        x: 10,
+        y: 20,
+        ..Default::default()
+    };
+    let mut right = panel(Rotation::None, false, false);
+    right.offset_x = 320;
+    let frame = DesktopFrame::from_panels(
+        &[TouchData::default(), data],
+        &[panel(Rotation::None, false, false), right],
+    );
+
+    let contact = frame.get(3).unwrap();
+    assert_eq!((contact.panel, contact.id), (1, 3));
+    assert_eq!(contact.point.status, TouchStatus::Touch);
+    assert_eq!((contact.point.x, contact.point.y), (330, 20));
+    assert_eq!(frame.get(0).unwrap().point.status, TouchStatus::Release);
+    assert_eq!(frame.get(4), None);
+
+    let ids: Vec<_> = frame.iter().map(|p| p.id).collect();
+    assert_eq!(ids, [0, 1, 2, 3]);
+}
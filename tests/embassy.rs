@@ -0,0 +1,157 @@
+//! `run_touch_task` against a mocked bus and INT pin.
+#![cfg(feature = "embassy")]
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_sync::channel::Channel;
+use embedded_hal::digital::{ErrorKind, ErrorType};
+use embedded_hal_async::digital::Wait;
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use ft6336u_dd::embassy::{Overflow, TouchTaskError, run_touch_task};
+use ft6336u_dd::{FT6336U_I2C_ADDRESS, Ft6336uAsync, TouchData, TouchStatus};
+
+type Touches = Channel<NoopRawMutex, TouchData, 1>;
+
+/// INT pin that fires `edges` times, then fails to stop the task. Before
+/// each listed edge it drains `channel` into `received`, like a consumer
+/// catching up.
+struct Int<'a> {
+    edges: usize,
+    drain_before: &'a [usize],
+    channel: &'a Touches,
+    received: Vec<TouchData>,
+    fired: usize,
+}
+
+impl Int<'_> {
+    fn edge(&mut self) -> Result<(), ErrorKind> {
+        if self.fired == self.edges {
+            return Err(ErrorKind::Other);
+        }
+        self.fired += 1;
+        if self.drain_before.contains(&self.fired) {
+            while let Ok(data) = self.channel.try_receive() {
+                self.received.push(data);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ErrorType for Int<'_> {
+    type Error = ErrorKind;
+}
+
+impl Wait for Int<'_> {
+    async fn wait_for_high(&mut self) -> Result<(), ErrorKind> {
+        self.edge()
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), ErrorKind> {
+        self.edge()
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), ErrorKind> {
+        self.edge()
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), ErrorKind> {
+        self.edge()
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), ErrorKind> {
+        self.edge()
+    }
+}
+
+/// Burst read with contact 0 at `(x, 100)`, or no contact.
+fn burst(x: Option<u8>) -> Transaction {
+    let mut bytes = vec![0; 14];
+    if let Some(x) = x {
+        bytes[1] = 1;
+        bytes[2] = 0x80;
+        bytes[3] = x;
+        bytes[5] = 100;
+    }
+    Transaction::write_read(FT6336U_I2C_ADDRESS, vec![0x01], bytes)
+}
+
+/// The mocks never suspend, so the task runs to its pin error in one poll.
+fn run<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    match future
+        .as_mut()
+        .poll(&mut Context::from_waker(Waker::noop()))
+    {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("task suspended"),
+    }
+}
+
+fn run_task(bursts: &[Transaction], drain_before: &[usize]) -> Vec<TouchData> {
+    let channel = Touches::new();
+    let mut bus = Mock::new(bursts);
+    let mut touch = Ft6336uAsync::new(bus.clone());
+    let mut int = Int {
+        edges: bursts.len(),
+        drain_before,
+        channel: &channel,
+        received: Vec::new(),
+        fired: 0,
+    };
+    let mut sender = channel.sender();
+    let result = run(run_touch_task(
+        &mut touch,
+        &mut int,
+        &mut sender,
+        Overflow::Drop,
+    ));
+    assert!(matches!(result, Err(TouchTaskError::Int(ErrorKind::Other))));
+    bus.done();
+
+    let mut received = int.received;
+    while let Ok(data) = channel.try_receive() {
+        received.push(data);
+    }
+    received
+}
+
+fn statuses(frames: &[TouchData]) -> Vec<TouchStatus> {
+    frames.iter().map(|d| d.points[0].status).collect()
+}
+
+#[test]
+fn release_dropped_by_full_channel_is_sent_again() {
+    // The release hits a full channel; the consumer then catches up and the
+    // next scan, reporting the same release, must still be delivered.
+    let received = run_task(&[burst(Some(10)), burst(None), burst(None)], &[3]);
+    assert_eq!(
+        statuses(&received),
+        [TouchStatus::Touch, TouchStatus::Release]
+    );
+}
+
+#[test]
+fn unchanged_frames_are_skipped() {
+    let received = run_task(
+        &[
+            burst(Some(10)),
+            burst(Some(10)),
+            burst(Some(10)),
+            burst(None),
+        ],
+        &[1, 2, 3, 4],
+    );
+    // The third scan repeats the second and is not sent.
+    assert_eq!(
+        statuses(&received),
+        [
+            TouchStatus::Touch,
+            TouchStatus::Stream,
+            TouchStatus::Release
+        ]
+    );
+}
@@ -0,0 +1,91 @@
+//! `FactoryMode` against scripted register values.
+
+use embedded_hal_mock::eh1::delay::NoopDelay;
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use ft6336u_dd::{FT6336U_I2C_ADDRESS, Ft6336u, Ft6336uError};
+
+const DEVICE_MODE: u8 = 0x00;
+/// `DeviceMode` with the mode field set to `Factory`.
+const FACTORY: u8 = 0x40;
+/// `Factory` with the start-scan bit set, as while a scan runs.
+const SCANNING: u8 = 0xC0;
+
+fn read(register: u8, bytes: &[u8]) -> Transaction {
+    Transaction::write_read(FT6336U_I2C_ADDRESS, vec![register], bytes.to_vec())
+}
+
+fn write(register: u8, value: u8) -> Transaction {
+    Transaction::write(FT6336U_I2C_ADDRESS, vec![register, value])
+}
+
+/// Start a scan that stays busy for `busy_polls` polls.
+fn rescan(busy_polls: usize) -> Vec<Transaction> {
+    let mut script = vec![read(DEVICE_MODE, &[FACTORY]), write(DEVICE_MODE, SCANNING)];
+    script.extend((0..busy_polls).map(|_| read(DEVICE_MODE, &[SCANNING])));
+    script.push(read(DEVICE_MODE, &[FACTORY]));
+    script
+}
+
+#[test]
+fn factory_session() {
+    let mut script = vec![
+        write(DEVICE_MODE, FACTORY),
+        read(0x02, &[2]), // TX channels
+        read(0x03, &[1]), // RX channels
+        write(0x06, 0),   // data type: raw
+    ];
+    script.extend(rescan(2));
+    script.extend([
+        write(0x01, 0), // line 0
+        read(0x10, &[0x01, 0x02, 0x03, 0x04, 0xFF, 0xFE]),
+        write(DEVICE_MODE, 0x00),
+    ]);
+    let mut mock = Mock::new(&script);
+    let mut touch = Ft6336u::new(mock.clone());
+
+    let mut factory = touch.enter_factory_mode(&mut NoopDelay).unwrap();
+    assert_eq!(factory.channel_count().unwrap(), 3);
+    let mut raw = [0u16; 3];
+    factory.read_raw_data(&mut NoopDelay, &mut raw).unwrap();
+    assert_eq!(raw, [0x0102, 0x0304, 0xFFFE]);
+    factory.exit().unwrap();
+
+    drop(touch);
+    mock.done();
+}
+
+#[test]
+fn dropping_the_guard_restores_working_mode() {
+    let mut mock = Mock::new(&[write(DEVICE_MODE, FACTORY), write(DEVICE_MODE, 0x00)]);
+    let mut touch = Ft6336u::new(mock.clone());
+
+    let factory = touch.enter_factory_mode(&mut NoopDelay).unwrap();
+    drop(factory);
+
+    drop(touch);
+    mock.done();
+}
+
+#[test]
+fn rescan_times_out_while_the_chip_stays_busy() {
+    let mut script = vec![
+        write(DEVICE_MODE, FACTORY),
+        read(DEVICE_MODE, &[FACTORY]),
+        write(DEVICE_MODE, SCANNING),
+    ];
+    script.extend((0..100).map(|_| read(DEVICE_MODE, &[SCANNING])));
+    script.push(write(DEVICE_MODE, 0x00));
+    let mut mock = Mock::new(&script);
+    let mut touch = Ft6336u::new(mock.clone());
+
+    let mut factory = touch.enter_factory_mode(&mut NoopDelay).unwrap();
+    assert!(matches!(
+        factory.rescan(&mut NoopDelay),
+        Err(Ft6336uError::Timeout(_))
+    ));
+    // The guard still restores working mode after the failed scan.
+    drop(factory);
+
+    drop(touch);
+    mock.done();
+}
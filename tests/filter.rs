@@ -0,0 +1,168 @@
+//! Host-side filters: rejected contacts must never reach consumers as a tap.
+
+use ft6336u_dd::TouchStatus::{Release, Stream, Touch};
+use ft6336u_dd::{
+    PalmAction, PalmFilter, PalmFilterConfig, TouchData, TouchPoint, TouchStatus, WaterFilter,
+    WaterFilterConfig,
+};
+
+/// One frame with contact 0 at `status` and the given weight and area.
+fn frame(status: TouchStatus, weight: u8, area: u8) -> TouchData {
+    let mut data = TouchData::default();
+    data.points[0] = TouchPoint {
+        status,
+        x: 100,
+        y: 200,
+        weight,
+        area,
+        palm: false,
+    };
+    data.touch_count = u8::from(status != TouchStatus::Release);
+    data
+}
+
+/// Contact 0's status after each frame.
+fn statuses(
+    apply: &mut impl FnMut(&TouchData) -> TouchData,
+    frames: &[TouchData],
+) -> Vec<TouchStatus> {
+    frames.iter().map(|f| apply(f).points[0].status).collect()
+}
+
+#[test]
+fn water_holds_clean_contact_until_settled() {
+    let mut water = WaterFilter::new(WaterFilterConfig {
+        settle_frames: 2,
+        ..Default::default()
+    });
+    let frames = [
+        frame(Touch, 60, 3),
+        frame(Stream, 62, 3),
+        frame(Stream, 61, 3),
+        frame(Stream, 63, 3),
+        frame(Release, 0, 0),
+    ];
+    assert_eq!(
+        statuses(&mut |f| water.apply(f), &frames),
+        [Release, Release, Touch, Stream, Release]
+    );
+}
+
+#[test]
+fn water_unstable_contact_never_forwarded() {
+    let mut water = WaterFilter::default();
+    // Weak enough to pass the shape check, but the weight swings on the
+    // second frame: the first frame must not leak out as a tap.
+    let frames = [
+        frame(Touch, 40, 3),
+        frame(Stream, 90, 3),
+        frame(Stream, 60, 3),
+        frame(Stream, 60, 3),
+        frame(Stream, 60, 3),
+        frame(Stream, 60, 3),
+        frame(Release, 0, 0),
+    ];
+    let out = statuses(&mut |f| water.apply(f), &frames);
+    assert!(out.iter().all(|&s| s == Release), "{out:?}");
+    assert_eq!(water.stats().rejected_unstable, 1);
+}
+
+#[test]
+fn water_shape_rejected_contact_never_forwarded() {
+    let mut water = WaterFilter::default();
+    let frames = [
+        frame(Touch, 10, 12),
+        frame(Stream, 10, 12),
+        frame(Release, 0, 0),
+    ];
+    let out = statuses(&mut |f| water.apply(f), &frames);
+    assert!(out.iter().all(|&s| s == Release), "{out:?}");
+    assert_eq!(water.stats().rejected_shape, 1);
+}
+
+#[test]
+fn water_does_not_release_forwarded_contact() {
+    let mut water = WaterFilter::new(WaterFilterConfig {
+        settle_frames: 0,
+        ..Default::default()
+    });
+    // A forwarded finger that later spreads into a weak, wide footprint keeps
+    // streaming until it lifts.
+    let frames = [
+        frame(Touch, 60, 3),
+        frame(Stream, 10, 12),
+        frame(Release, 0, 0),
+    ];
+    assert_eq!(
+        statuses(&mut |f| water.apply(f), &frames),
+        [Touch, Stream, Release]
+    );
+}
+
+#[test]
+fn palm_growing_after_first_frame_never_forwarded() {
+    let mut palm = PalmFilter::default();
+    // The palm's first frame looks like a finger.
+    let frames = [
+        frame(Touch, 80, 4),
+        frame(Stream, 220, 14),
+        frame(Stream, 230, 15),
+        frame(Stream, 230, 15),
+        frame(Release, 0, 0),
+    ];
+    let out = statuses(&mut |f| palm.apply(f), &frames);
+    assert!(out.iter().all(|&s| s == Release), "{out:?}");
+    assert_eq!(palm.rejected(), 1);
+}
+
+#[test]
+fn palm_finger_forwarded_after_hold() {
+    let mut palm = PalmFilter::default();
+    let frames = [
+        frame(Touch, 80, 4),
+        frame(Stream, 82, 4),
+        frame(Stream, 81, 4),
+        frame(Stream, 83, 4),
+        frame(Release, 0, 0),
+    ];
+    assert_eq!(
+        statuses(&mut |f| palm.apply(f), &frames),
+        [Release, Release, Touch, Stream, Release]
+    );
+    assert_eq!(palm.rejected(), 0);
+}
+
+#[test]
+fn palm_flag_mode_forwards_flagged_after_hold() {
+    let mut palm = PalmFilter::new(PalmFilterConfig {
+        action: PalmAction::Flag,
+        ..Default::default()
+    });
+    let frames = [
+        frame(Touch, 80, 4),
+        frame(Stream, 220, 14),
+        frame(Stream, 230, 15),
+    ];
+    let out: Vec<_> = frames.iter().map(|f| palm.apply(f).points[0]).collect();
+    assert_eq!(out[1].status, Release);
+    assert_eq!(out[2].status, Touch);
+    assert!(out[2].palm);
+}
+
+#[test]
+fn palm_late_growth_flags_without_releasing() {
+    let mut palm = PalmFilter::new(PalmFilterConfig {
+        hold_frames: 0,
+        ..Default::default()
+    });
+    let frames = [
+        frame(Touch, 80, 4),
+        frame(Stream, 220, 14),
+        frame(Release, 0, 0),
+    ];
+    let out: Vec<_> = frames.iter().map(|f| palm.apply(f).points[0]).collect();
+    assert_eq!(out[0].status, Touch);
+    assert_eq!(out[1].status, Stream);
+    assert!(out[1].palm);
+    assert_eq!(out[2].status, Release);
+}
@@ -0,0 +1,59 @@
+//! Frame numbering: sequence numbers are stamped when a burst is read.
+
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use ft6336u_dd::{FT6336U_I2C_ADDRESS, Ft6336u, Ft6336uState, ManualClock, RawFrame, TouchStatus};
+
+/// Burst read of 0x01-0x0E with one contact at (x, 100).
+fn burst(x: u8) -> Transaction {
+    let mut bytes = vec![0; 14];
+    bytes[1] = 1;
+    bytes[2] = 0x80; // Contact event
+    bytes[3] = x;
+    bytes[5] = 100;
+    Transaction::write_read(FT6336U_I2C_ADDRESS, vec![0x01], bytes)
+}
+
+#[test]
+fn read_raw_frame_numbers_bursts() {
+    let mut mock = Mock::new(&[burst(1), burst(2), burst(3)]);
+    let mut touch = Ft6336u::new(mock.clone());
+    let sequences: Vec<_> = (0..3)
+        .map(|_| touch.read_raw_frame().unwrap().sequence)
+        .collect();
+    assert_eq!(sequences, [0, 1, 2]);
+    let (_, state) = touch.into_parts();
+    assert_eq!(state.sequence(), 3);
+    mock.done();
+}
+
+#[test]
+fn frames_lost_after_read_leave_a_gap() {
+    let mut mock = Mock::new(&[burst(1), burst(2), burst(3)]);
+    let mut touch = Ft6336u::new(mock.clone());
+    let frames: Vec<RawFrame> = (0..3).map(|_| touch.read_raw_frame().unwrap()).collect();
+    mock.done();
+
+    // The middle frame is lost, e.g. in a full queue; the decoder sees the gap.
+    let mut state = Ft6336uState::new();
+    let first = state.update(&frames[0]);
+    let last = state.update(&frames[2]);
+    assert_eq!(frames[2].sequence.wrapping_sub(frames[0].sequence), 2);
+    assert_eq!(first.points[0].status, TouchStatus::Touch);
+    assert_eq!(last.points[0].x, 3);
+    // Decoding does not consume sequence numbers.
+    assert_eq!(state.sequence(), 0);
+}
+
+#[test]
+fn scan_timestamped_carries_read_sequence() {
+    let mut mock = Mock::new(&[burst(1), burst(2)]);
+    let mut touch = Ft6336u::new(mock.clone());
+    let mut clock = ManualClock::new(1_000);
+    // Read but never decoded.
+    touch.read_raw_frame().unwrap();
+    let frame = touch.scan_timestamped(&mut clock).unwrap();
+    assert_eq!(frame.sequence, 1);
+    assert_eq!(frame.timestamp_us, 1_000);
+    assert_eq!(frame.data.points[0].status, TouchStatus::Touch);
+    mock.done();
+}
@@ -0,0 +1,88 @@
+//! Virtual key press/release debouncing.
+
+use ft6336u_dd::TouchStatus::{Release, Stream, Touch};
+use ft6336u_dd::{KeyEvent, Rect, TouchData, TouchPoint, TouchStatus, VirtualKeys};
+
+const KEY: u16 = 1;
+
+fn frame(status: TouchStatus, x: u16, y: u16) -> TouchData {
+    let mut data = TouchData::default();
+    data.points[0] = TouchPoint {
+        status,
+        x,
+        y,
+        ..Default::default()
+    };
+    data.touch_count = u8::from(status != Release);
+    data
+}
+
+fn keys() -> VirtualKeys<1> {
+    let mut keys = VirtualKeys::new(2);
+    keys.add(Rect::new(0, 480, 100, 40), KEY).unwrap();
+    keys
+}
+
+fn events(keys: &mut VirtualKeys<1>, frames: &[TouchData]) -> Vec<Vec<KeyEvent>> {
+    frames
+        .iter()
+        .map(|f| keys.process(f).events.to_vec())
+        .collect()
+}
+
+#[test]
+fn releases_on_the_lift_frame() {
+    let mut keys = keys();
+    // With INT-triggered scanning nothing arrives after the lift.
+    let out = events(
+        &mut keys,
+        &[
+            frame(Touch, 50, 500),
+            frame(Stream, 50, 500),
+            frame(Release, 50, 500),
+        ],
+    );
+    assert_eq!(
+        out,
+        [
+            vec![],
+            vec![KeyEvent::Pressed(KEY)],
+            vec![KeyEvent::Released(KEY)],
+        ]
+    );
+}
+
+#[test]
+fn debounces_sliding_off_while_down() {
+    let mut keys = keys();
+    let out = events(
+        &mut keys,
+        &[
+            frame(Touch, 50, 500),
+            frame(Stream, 50, 500),
+            frame(Stream, 150, 500),
+            frame(Stream, 50, 500),
+            frame(Stream, 150, 500),
+            frame(Stream, 150, 500),
+        ],
+    );
+    assert_eq!(
+        out,
+        [
+            vec![],
+            vec![KeyEvent::Pressed(KEY)],
+            vec![],
+            vec![],
+            vec![],
+            vec![KeyEvent::Released(KEY)],
+        ]
+    );
+}
+
+#[test]
+fn key_contacts_are_removed_from_pointer() {
+    let mut keys = keys();
+    let out = keys.process(&frame(Touch, 50, 500));
+    assert_eq!(out.pointer.points[0].status, Release);
+    assert_eq!(out.pointer.touch_count, 0);
+}
@@ -0,0 +1,120 @@
+//! Velocity fitting and flings in `MotionTracker`, fed frames stamped by a
+//! `ManualClock`.
+
+use ft6336u_dd::TouchStatus::{Release, Stream, Touch};
+use ft6336u_dd::{
+    Clock, ManualClock, MotionTracker, TouchData, TouchFrame, TouchPoint, TouchStatus, Vector,
+};
+
+/// Frame interval in microseconds.
+const FRAME_US: u64 = 10_000;
+
+/// Feeds one contact through a tracker, one frame per `FRAME_US`.
+struct Replay {
+    clock: ManualClock,
+    tracker: MotionTracker,
+    sequence: u32,
+}
+
+impl Replay {
+    fn new() -> Self {
+        Self {
+            clock: ManualClock::new(1_000_000),
+            tracker: MotionTracker::default(),
+            sequence: 0,
+        }
+    }
+
+    fn frame(&mut self, status: TouchStatus, x: u16, y: u16) {
+        self.frame_after(FRAME_US, status, x, y);
+    }
+
+    fn frame_after(&mut self, us: u64, status: TouchStatus, x: u16, y: u16) {
+        self.clock.advance(us);
+        self.sequence += 1;
+        let mut data = TouchData::default();
+        data.points[0] = TouchPoint {
+            status,
+            x,
+            y,
+            ..Default::default()
+        };
+        data.touch_count = u8::from(status != Release);
+        self.tracker.update_frame(&TouchFrame {
+            data,
+            timestamp_us: self.clock.now_us(),
+            sequence: self.sequence,
+        });
+    }
+
+    /// Touch down and move through `path`, one point per frame.
+    fn drag(&mut self, path: &[(u16, u16)]) {
+        for (i, &(x, y)) in path.iter().enumerate() {
+            self.frame(if i == 0 { Touch } else { Stream }, x, y);
+        }
+    }
+}
+
+fn assert_close(actual: Vector, expected: (f32, f32), tolerance: f32) {
+    assert!(
+        (actual.x - expected.0).abs() <= tolerance && (actual.y - expected.1).abs() <= tolerance,
+        "{actual:?} is not within {tolerance} of {expected:?}"
+    );
+}
+
+#[test]
+fn constant_velocity_fits_without_acceleration() {
+    let mut replay = Replay::new();
+    // 5 px right and 2 px up per 10 ms frame, far from the origin.
+    let path: Vec<_> = (0..6).map(|i| (3000 + 5 * i, 3000 - 2 * i)).collect();
+    replay.drag(&path);
+
+    let motion = replay.tracker.motion(0).unwrap();
+    assert_close(motion.velocity, (500.0, -200.0), 0.5);
+    assert_close(motion.acceleration, (0.0, 0.0), 5.0);
+}
+
+#[test]
+fn constant_acceleration_is_recovered() {
+    let mut replay = Replay::new();
+    // x = 10000 t^2 from rest: 20000 px/s^2, 1000 px/s after 50 ms.
+    let path: Vec<_> = (0..6).map(|i: u16| (100 + i * i, 100)).collect();
+    replay.drag(&path);
+
+    let motion = replay.tracker.motion(0).unwrap();
+    assert_close(motion.velocity, (1000.0, 0.0), 1.0);
+    assert_close(motion.acceleration, (20000.0, 0.0), 50.0);
+}
+
+#[test]
+fn fling_is_clamped_to_the_maximum_speed() {
+    let mut replay = Replay::new();
+    // 6000 px/s right and 8000 px/s down: 10000 px/s overall.
+    let path: Vec<_> = (0..4).map(|i| (100 + 60 * i, 100 + 80 * i)).collect();
+    replay.drag(&path);
+    replay.frame(Release, 280, 340);
+
+    // Scaled to the default maximum of 8000 px/s, keeping the direction.
+    let fling = replay.tracker.take_fling(0).unwrap();
+    assert_close(fling, (4800.0, 6400.0), 0.5);
+    assert_eq!(replay.tracker.take_fling(0), None);
+    assert_eq!(replay.tracker.fling(0), None);
+}
+
+#[test]
+fn slow_or_paused_contacts_do_not_fling() {
+    let mut slow = Replay::new();
+    // 1 px per 30 ms is below the default 50 px/s.
+    slow.frame(Touch, 100, 100);
+    for x in 101..104 {
+        slow.frame_after(30_000, Stream, x, 100);
+    }
+    slow.frame(Release, 103, 100);
+    assert_eq!(slow.tracker.fling(0), None);
+
+    let mut paused = Replay::new();
+    paused.drag(&[(100, 100), (200, 100), (300, 100)]);
+    // Resting longer than `max_pause_us` before lifting.
+    paused.frame_after(50_000, Release, 300, 100);
+    assert_eq!(paused.tracker.fling(0), None);
+}
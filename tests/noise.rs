@@ -0,0 +1,89 @@
+//! Ghost-touch counting in `NoiseMonitor`.
+
+use ft6336u_dd::TouchStatus::{Release, Stream, Touch};
+use ft6336u_dd::{NoiseMonitor, NoiseMonitorConfig, TouchData, TouchPoint, TouchStatus};
+
+fn frame(status: TouchStatus, x: u16, y: u16) -> TouchData {
+    let mut data = TouchData::default();
+    data.points[0] = TouchPoint {
+        status,
+        x,
+        y,
+        ..Default::default()
+    };
+    data.touch_count = u8::from(status != Release);
+    data
+}
+
+fn monitor(trigger_events: u8, window_frames: u16) -> NoiseMonitor {
+    NoiseMonitor::new(NoiseMonitorConfig {
+        trigger_events,
+        window_frames,
+        ..NoiseMonitorConfig::default()
+    })
+}
+
+/// `observe()` result for each frame.
+fn observe(monitor: &mut NoiseMonitor, frames: &[TouchData]) -> Vec<bool> {
+    frames.iter().map(|f| monitor.observe(f)).collect()
+}
+
+#[test]
+fn short_contact_is_a_ghost() {
+    let mut short = monitor(1, 100);
+    let frames = [frame(Touch, 10, 10), frame(Release, 10, 10)];
+    assert_eq!(observe(&mut short, &frames), [false, true]);
+
+    // Held for `min_contact_frames`, the same contact is genuine.
+    let mut held = monitor(1, 100);
+    let frames = [
+        frame(Touch, 10, 10),
+        frame(Stream, 12, 10),
+        frame(Release, 12, 10),
+    ];
+    assert_eq!(observe(&mut held, &frames), [false; 3]);
+    assert!(!held.is_triggered());
+}
+
+#[test]
+fn jump_is_a_ghost() {
+    let mut monitor = monitor(1, 100);
+    let frames = [
+        frame(Touch, 0, 0),
+        frame(Stream, 200, 200),
+        frame(Stream, 401, 200),
+    ];
+    // 200 is the default `max_jump`; only going past it counts.
+    assert_eq!(observe(&mut monitor, &frames), [false, false, true]);
+}
+
+#[test]
+fn events_expire_with_the_window() {
+    let ghost = [frame(Touch, 10, 10), frame(Release, 10, 10)];
+    let idle = frame(Release, 10, 10);
+    let frames = [ghost[0], ghost[1], idle, ghost[0], ghost[1]];
+
+    // The window closes after the third frame, dropping the first ghost.
+    let mut short_window = monitor(2, 3);
+    assert_eq!(observe(&mut short_window, &frames), [false; 5]);
+
+    let mut long_window = monitor(2, 10);
+    assert_eq!(
+        observe(&mut long_window, &frames),
+        [false, false, false, false, true]
+    );
+}
+
+#[test]
+fn observe_triggers_once_until_reset() {
+    let ghost = [frame(Touch, 10, 10), frame(Release, 10, 10)];
+    let mut monitor = monitor(1, 100);
+
+    assert_eq!(observe(&mut monitor, &ghost), [false, true]);
+    assert_eq!(observe(&mut monitor, &ghost), [false, false]);
+    assert!(monitor.is_triggered());
+
+    monitor.reset();
+    assert!(!monitor.is_triggered());
+    assert_eq!(observe(&mut monitor, &ghost), [false, true]);
+}
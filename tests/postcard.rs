@@ -0,0 +1,127 @@
+//! Postcard round trips of the serializable types.
+#![cfg(feature = "postcard")]
+
+use core::fmt::Debug;
+
+use ft6336u_dd::{
+    DeviceMode, GestureId, PalmAction, PalmFilterConfig, PowerModeEnum, ProximityState, RawFrame,
+    TouchData, TouchFrame, TouchPoint, TouchStatus, TunerConfig, TuningPreset,
+};
+use postcard::experimental::max_size::MaxSize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Encode `value`, check the length against `POSTCARD_MAX_SIZE` and decode
+/// it again.
+fn round_trip<T>(value: &T) -> T
+where
+    T: Serialize + DeserializeOwned + MaxSize + PartialEq + Debug,
+{
+    let mut buffer = [0u8; 256];
+    let encoded = postcard::to_slice(value, &mut buffer).unwrap();
+    assert!(
+        encoded.len() <= T::POSTCARD_MAX_SIZE,
+        "{value:?} encodes to {} bytes, POSTCARD_MAX_SIZE is {}",
+        encoded.len(),
+        T::POSTCARD_MAX_SIZE
+    );
+    let decoded: T = postcard::from_bytes(encoded).unwrap();
+    assert_eq!(&decoded, value);
+    decoded
+}
+
+/// Largest values in every field, for the longest varint encodings.
+fn touch_data() -> TouchData {
+    let point = TouchPoint {
+        status: TouchStatus::Stream,
+        x: u16::MAX,
+        y: u16::MAX,
+        weight: u8::MAX,
+        area: 0x0F,
+        palm: true,
+    };
+    TouchData {
+        touch_count: 2,
+        points: [
+            point,
+            TouchPoint {
+                status: TouchStatus::Touch,
+                ..point
+            },
+        ],
+        proximity: Some(ProximityState::Far),
+    }
+}
+
+#[test]
+fn touch_data_round_trip() {
+    round_trip(&touch_data());
+    round_trip(&TouchData::default());
+}
+
+#[test]
+fn touch_frame_round_trip() {
+    round_trip(&TouchFrame {
+        data: touch_data(),
+        timestamp_us: u64::MAX,
+        sequence: u32::MAX,
+    });
+    round_trip(&TouchFrame::default());
+}
+
+#[test]
+fn raw_frame_round_trip() {
+    round_trip(&RawFrame {
+        bytes: [0xFF; 14],
+        sequence: u32::MAX,
+    });
+    round_trip(&RawFrame::default());
+}
+
+#[test]
+fn config_round_trip() {
+    for preset in [
+        TuningPreset::Normal,
+        TuningPreset::Glove,
+        TuningPreset::WetFinger,
+    ] {
+        round_trip(&TunerConfig::preset(preset));
+    }
+    round_trip(&TunerConfig {
+        window_frames: u16::MAX,
+        ..TunerConfig::default()
+    });
+    round_trip(&PalmFilterConfig {
+        action: PalmAction::Flag,
+        ..PalmFilterConfig::default()
+    });
+}
+
+#[test]
+fn generated_enums_round_trip_as_field_value() {
+    for mode in [
+        DeviceMode::Working,
+        DeviceMode::Factory,
+        DeviceMode::Unknown,
+    ] {
+        round_trip(&mode);
+    }
+    round_trip(&GestureId::Unknown);
+    round_trip(&PowerModeEnum::Unknown);
+
+    // Encoded as the field value, not the register byte.
+    let mut buffer = [0u8; 1];
+    assert_eq!(
+        postcard::to_slice(&GestureId::ProximityNear, &mut buffer).unwrap(),
+        [0xC0]
+    );
+    assert_eq!(
+        postcard::to_slice(&DeviceMode::Factory, &mut buffer).unwrap(),
+        [4]
+    );
+    // Values the register map doesn't name decode as `Unknown`.
+    assert_eq!(
+        postcard::from_bytes::<GestureId>(&[0x42]).unwrap(),
+        GestureId::Unknown
+    );
+}
@@ -0,0 +1,120 @@
+//! Quirk table lookup, coordinate correction and `init_with_quirks()`.
+
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use ft6336u_dd::quirks::lookup;
+use ft6336u_dd::{
+    FT6336U_I2C_ADDRESS, FirmwareIdentity, Ft6336uError, Ft6336uState, QuirkEntry, Quirks,
+};
+
+const FLIPPED: Quirks = Quirks {
+    flip_x: true,
+    max_x: 320,
+    max_y: 480,
+    unsupported_registers: &[0x80],
+    ..Quirks::NONE
+};
+
+const BROKEN: Quirks = Quirks {
+    unusable: Some("firmware 0x01 drops touches"),
+    ..Quirks::NONE
+};
+
+const TABLE: &[QuirkEntry] = &[
+    QuirkEntry {
+        chip_id: Some(0x64),
+        firmware_ids: (0x01, 0x01),
+        library_version: None,
+        release_code_id: None,
+        quirks: BROKEN,
+    },
+    QuirkEntry {
+        chip_id: None,
+        firmware_ids: (0x01, 0x10),
+        library_version: Some(0x1002),
+        release_code_id: None,
+        quirks: FLIPPED,
+    },
+];
+
+fn id(chip_id: u8, firmware_id: u8, library_version: u16) -> FirmwareIdentity {
+    FirmwareIdentity {
+        chip_id,
+        firmware_id,
+        library_version,
+        release_code_id: 1,
+    }
+}
+
+/// Identity register reads answering with `id`.
+fn identity(id: FirmwareIdentity) -> Vec<Transaction> {
+    let read = |register, bytes: &[u8]| {
+        Transaction::write_read(FT6336U_I2C_ADDRESS, vec![register], bytes.to_vec())
+    };
+    vec![
+        read(0xA3, &[id.chip_id]),
+        read(0xA6, &[id.firmware_id]),
+        read(0xA1, &id.library_version.to_be_bytes()),
+        read(0xAF, &[id.release_code_id]),
+    ]
+}
+
+#[test]
+fn lookup_takes_the_first_matching_entry() {
+    // Matches both rows; the first wins.
+    assert_eq!(lookup(TABLE, &id(0x64, 0x01, 0x1002)), BROKEN);
+    // `None` fields match anything, and the firmware range is inclusive.
+    assert_eq!(lookup(TABLE, &id(0x99, 0x01, 0x1002)), FLIPPED);
+    assert_eq!(lookup(TABLE, &id(0x64, 0x10, 0x1002)), FLIPPED);
+    // Outside the range or with another library version nothing applies.
+    assert_eq!(lookup(TABLE, &id(0x64, 0x11, 0x1002)), Quirks::NONE);
+    assert_eq!(lookup(TABLE, &id(0x64, 0x05, 0x1003)), Quirks::NONE);
+    assert_eq!(lookup(&[], &id(0x64, 0x01, 0x1002)), Quirks::NONE);
+}
+
+#[test]
+fn correct_clamps_then_flips_each_axis() {
+    assert_eq!(FLIPPED.correct(20, 30), (300, 30));
+    // Out-of-range coordinates are clamped, not rescaled.
+    assert_eq!(FLIPPED.correct(1000, 1000), (0, 480));
+    assert_eq!(FLIPPED.correct_x(320), 0);
+    assert_eq!(FLIPPED.correct_y(500), 480);
+    assert_eq!(Quirks::NONE.correct(4000, 5000), (4000, 0x0FFF));
+}
+
+#[test]
+fn is_supported_checks_the_register_list() {
+    assert!(!FLIPPED.is_supported(0x80));
+    assert!(FLIPPED.is_supported(0x81));
+    assert!(Quirks::NONE.is_supported(0x80));
+}
+
+#[test]
+fn init_with_quirks_applies_the_matching_entry() {
+    let firmware = id(0x64, 0x05, 0x1002);
+    let mut mock = Mock::new(&identity(firmware));
+    let mut state = Ft6336uState::new();
+
+    let found = state.attach(&mut mock).init_with_quirks(TABLE).unwrap();
+    assert_eq!(found, firmware);
+    assert_eq!(*state.quirks(), FLIPPED);
+    // Ignored registers are refused without touching the bus.
+    assert!(matches!(
+        state.attach(&mut mock).write_touch_threshold(40),
+        Err(Ft6336uError::NotSupported(_))
+    ));
+    mock.done();
+}
+
+#[test]
+fn init_with_quirks_refuses_unusable_firmware() {
+    let mut mock = Mock::new(&identity(id(0x64, 0x01, 0x1002)));
+    let mut state = Ft6336uState::new();
+
+    assert!(matches!(
+        state.attach(&mut mock).init_with_quirks(TABLE),
+        Err(Ft6336uError::NotSupported("firmware 0x01 drops touches"))
+    ));
+    // The quirks are still recorded, so later calls see them.
+    assert_eq!(*state.quirks(), BROKEN);
+    mock.done();
+}
@@ -0,0 +1,100 @@
+//! Freshness detection and rate measurement in `RateMonitor`.
+
+use ft6336u_dd::{RateMonitor, RawFrame};
+
+/// Burst with `touches` contacts, contact 0 at `x`.
+fn burst(touches: u8, x: u8) -> RawFrame {
+    let mut bytes = [0; 14];
+    bytes[1] = touches;
+    bytes[3] = x;
+    RawFrame { bytes, sequence: 0 }
+}
+
+#[test]
+fn repeated_bursts_are_stale_while_touched() {
+    let mut monitor = RateMonitor::new();
+    assert!(monitor.observe(&burst(1, 10), 0));
+    assert!(!monitor.observe(&burst(1, 10), 10_000));
+    assert!(!monitor.observe(&burst(1, 10), 20_000));
+
+    let stats = monitor.stats();
+    assert_eq!((stats.scans, stats.fresh, stats.stale_streak), (3, 1, 2));
+    assert_eq!(stats.last_fresh_us, 0);
+    assert!(stats.is_stalled(20_000, 15_000));
+    assert!(!stats.is_stalled(20_000, 20_000));
+
+    // A new report ends the streak.
+    assert!(monitor.observe(&burst(1, 11), 30_000));
+    assert_eq!(monitor.stats().stale_streak, 0);
+    assert!(!monitor.stats().is_stalled(100_000, 15_000));
+}
+
+#[test]
+fn repeated_bursts_without_contact_are_not_a_streak() {
+    let mut monitor = RateMonitor::new();
+    for t in 0..3 {
+        monitor.observe(&burst(0, 0), t * 10_000);
+    }
+    let stats = monitor.stats();
+    assert_eq!((stats.fresh, stats.stale_streak), (1, 0));
+    assert!(!stats.is_stalled(1_000_000, 15_000));
+}
+
+#[test]
+fn interrupt_marks_the_next_burst_fresh() {
+    let mut monitor = RateMonitor::new();
+    monitor.observe(&burst(1, 10), 0);
+
+    // A finger held still: same registers, but the chip did report.
+    monitor.interrupt();
+    monitor.interrupt();
+    assert!(monitor.observe(&burst(1, 10), 10_000));
+    // Both edges are used up by one burst.
+    assert!(!monitor.observe(&burst(1, 10), 20_000));
+
+    let stats = monitor.stats();
+    assert_eq!(
+        (stats.interrupts, stats.fresh, stats.stale_streak),
+        (2, 2, 1)
+    );
+    assert_eq!(stats.last_fresh_us, 10_000);
+}
+
+#[test]
+fn measures_fresh_reports_per_second() {
+    let mut monitor = RateMonitor::new();
+    monitor.set_expected_rates(100, 25);
+    // Two seconds of bursts every 5 ms, every other one fresh.
+    for i in 0..=400u64 {
+        monitor.observe(&burst(1, (i / 2) as u8), i * 5_000);
+    }
+
+    let stats = monitor.stats();
+    assert_eq!(stats.measured_hz, 100);
+    assert_eq!(stats.expected_hz, 100);
+    assert!(!stats.is_slow(90));
+
+    // Untouched, the monitor-mode rate is expected instead.
+    monitor.observe(&burst(0, 0), 2_005_000);
+    assert_eq!(monitor.stats().expected_hz, 25);
+
+    monitor.reset();
+    assert_eq!(monitor.stats().scans, 0);
+    monitor.observe(&burst(1, 0), 0);
+    assert_eq!(monitor.stats().expected_hz, 100);
+}
+
+#[test]
+fn slow_reporting_is_flagged() {
+    let mut monitor = RateMonitor::new();
+    monitor.set_expected_rates(100, 25);
+    // A report every 20 ms for two windows.
+    for i in 0..=100u64 {
+        monitor.observe(&burst(1, i as u8), i * 20_000);
+    }
+
+    let stats = monitor.stats();
+    assert_eq!(stats.measured_hz, 50);
+    assert!(stats.is_slow(90));
+    assert!(!stats.is_slow(50));
+}
@@ -0,0 +1,161 @@
+//! Capture, drag, release and z-order in `RegionMap`.
+
+use ft6336u_dd::RegionEvent::{Dragged, Pressed, Released};
+use ft6336u_dd::TouchStatus::{Release, Stream, Touch};
+use ft6336u_dd::{Circle, Rect, RegionEvent, RegionMap, TouchData, TouchPoint, TouchStatus};
+
+const BUTTON: u16 = 1;
+const KNOB: u16 = 2;
+const OVERLAY: u16 = 3;
+
+fn frame(status: TouchStatus, x: u16, y: u16) -> TouchData {
+    let mut data = TouchData::default();
+    data.points[0] = TouchPoint {
+        status,
+        x,
+        y,
+        ..Default::default()
+    };
+    data.touch_count = u8::from(status != Release);
+    data
+}
+
+fn events<const N: usize>(ui: &mut RegionMap<N>, frames: &[TouchData]) -> Vec<Vec<RegionEvent>> {
+    frames.iter().map(|f| ui.process(f).to_vec()).collect()
+}
+
+/// A button with a knob overlapping its right edge on top.
+fn ui() -> RegionMap<4> {
+    let mut ui = RegionMap::new();
+    ui.add(BUTTON, Rect::new(0, 0, 100, 50), 0).unwrap();
+    ui.add(KNOB, Circle::new(100, 25, 20), 1).unwrap();
+    ui
+}
+
+#[test]
+fn press_captures_until_release() {
+    let mut ui = ui();
+    let frames = [
+        frame(Touch, 10, 10),
+        frame(Stream, 10, 10),
+        frame(Stream, 200, 10),
+        // Dragged across the knob, still reporting to the button.
+        frame(Stream, 99, 25),
+        frame(Release, 99, 25),
+    ];
+    let (region, id) = (BUTTON, 0);
+    assert_eq!(
+        events(&mut ui, &frames),
+        [
+            vec![Pressed {
+                region,
+                id,
+                x: 10,
+                y: 10
+            }],
+            vec![],
+            vec![Dragged {
+                region,
+                id,
+                x: 200,
+                y: 10,
+                inside: false
+            }],
+            vec![Dragged {
+                region,
+                id,
+                x: 99,
+                y: 25,
+                inside: true
+            }],
+            vec![Released {
+                region,
+                id,
+                x: 99,
+                y: 25,
+                inside: true
+            }],
+        ]
+    );
+}
+
+#[test]
+fn release_outside_reports_a_cancelled_press() {
+    let mut ui = ui();
+    let frames = [
+        frame(Touch, 10, 10),
+        frame(Stream, 10, 80),
+        frame(Release, 10, 80),
+    ];
+    let last = events(&mut ui, &frames).pop().unwrap();
+    assert_eq!(
+        last,
+        [Released {
+            region: BUTTON,
+            id: 0,
+            x: 10,
+            y: 80,
+            inside: false
+        }]
+    );
+}
+
+#[test]
+fn topmost_region_wins() {
+    let mut ui = ui();
+    // Inside both; the knob is on top.
+    assert_eq!(ui.hit(95, 25).map(|r| r.id), Some(KNOB));
+    assert_eq!(ui.hit(50, 25).map(|r| r.id), Some(BUTTON));
+    assert_eq!(ui.hit(300, 300), None);
+
+    // Among equal z the region added last is on top.
+    ui.add(OVERLAY, Rect::new(90, 0, 20, 50), 1).unwrap();
+    assert_eq!(ui.hit(95, 25).map(|r| r.id), Some(OVERLAY));
+    assert_eq!(
+        ui.process(&frame(Touch, 95, 25)).to_vec(),
+        [Pressed {
+            region: OVERLAY,
+            id: 0,
+            x: 95,
+            y: 25
+        }]
+    );
+}
+
+#[test]
+fn contacts_outside_every_region_are_ignored() {
+    let mut ui = ui();
+    let frames = [
+        frame(Touch, 300, 300),
+        frame(Stream, 10, 10),
+        frame(Release, 10, 10),
+    ];
+    assert!(events(&mut ui, &frames).iter().all(Vec::is_empty));
+}
+
+#[test]
+fn removing_a_region_drops_its_capture() {
+    let mut ui = ui();
+    ui.process(&frame(Touch, 10, 10));
+    ui.remove(BUTTON);
+    let frames = [frame(Stream, 20, 10), frame(Release, 20, 10)];
+    assert!(events(&mut ui, &frames).iter().all(Vec::is_empty));
+    assert_eq!(ui.regions().len(), 1);
+}
+
+#[test]
+fn full_map_returns_the_region() {
+    let mut ui: RegionMap<1> = RegionMap::new();
+    ui.add(BUTTON, Rect::new(0, 0, 10, 10), 0).unwrap();
+    let rejected = ui.add(KNOB, Circle::new(5, 5, 5), 0).unwrap_err();
+    assert_eq!(rejected.id, KNOB);
+}
+
+#[test]
+fn circle_contains_far_from_the_origin() {
+    assert!(!Circle::new(60000, 60000, 10).contains(0, 0));
+    assert!(Circle::new(60000, 60000, 10).contains(60006, 60008));
+    assert!(!Circle::new(60000, 60000, 10).contains(60006, 60009));
+    assert!(Circle::new(0, 0, u16::MAX).contains(u16::MAX, 0));
+    assert!(!Circle::new(0, 0, u16::MAX).contains(u16::MAX, 1));
+}
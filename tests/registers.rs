@@ -0,0 +1,109 @@
+//! `registers::REGISTERS` is generated by build.rs from `device.yaml`
+//! independently of `create_device!`. Check both agree on every register's
+//! address and size.
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::convert::Infallible;
+use std::rc::Rc;
+
+use device_driver::RegisterInterface;
+use ft6336u_dd::Ft6336uLowLevel;
+use ft6336u_dd::registers::{REGISTERS, find_by_name};
+
+/// Records the address and size of the last register access. The record is
+/// shared, since the generated `interface()` accessor is not public.
+#[derive(Default)]
+struct Recorder {
+    last: Rc<RefCell<Option<(u8, u32)>>>,
+}
+
+impl RegisterInterface for Recorder {
+    type Error = Infallible;
+    type AddressType = u8;
+
+    fn write_register(&mut self, _: u8, _: u32, _: &[u8]) -> Result<(), Infallible> {
+        unreachable!("the check only reads")
+    }
+
+    fn read_register(
+        &mut self,
+        address: u8,
+        size_bits: u32,
+        data: &mut [u8],
+    ) -> Result<(), Infallible> {
+        data.fill(0);
+        *self.last.borrow_mut() = Some((address, size_bits));
+        Ok(())
+    }
+}
+
+#[test]
+fn register_table_matches_generated_device() {
+    let recorder = Recorder::default();
+    let last = Rc::clone(&recorder.last);
+    let mut ll = Ft6336uLowLevel::new(recorder);
+    let mut checked = BTreeSet::new();
+
+    macro_rules! check {
+        ($name:literal, $($op:tt)+) => {{
+            ll.$($op)+.read().unwrap();
+            let generated = last.borrow_mut().take().unwrap();
+            let info = find_by_name($name).unwrap_or_else(|| panic!("{} missing", $name));
+            assert_eq!((info.address, info.size_bits), generated, "{}", $name);
+            checked.insert($name);
+        }};
+    }
+
+    check!("DeviceMode", device_mode());
+    check!("GestureId", gesture_id());
+    check!("TdStatus", td_status());
+    check!("Tp0.XEvent", tp(0).xevent());
+    check!("Tp0.YId", tp(0).yid());
+    check!("Tp0.Weight", tp(0).weight());
+    check!("Tp0.Misc", tp(0).misc());
+    check!("Tp1.XEvent", tp(1).xevent());
+    check!("Tp1.YId", tp(1).yid());
+    check!("Tp1.Weight", tp(1).weight());
+    check!("Tp1.Misc", tp(1).misc());
+    check!("FactoryLineNum", factory_line_num());
+    check!("FactoryTxNum", factory_tx_num());
+    check!("FactoryRxNum", factory_rx_num());
+    check!("FactoryDataType", factory_data_type());
+    check!("FactoryRawData", factory_raw_data());
+    check!("Threshold", threshold());
+    check!("ThPeak", th_peak());
+    check!("ThCal", th_cal());
+    check!("ThWater", th_water());
+    check!("ThTemp", th_temp());
+    check!("FilterCoefficient", filter_coefficient());
+    check!("Ctrl", ctrl());
+    check!("TimeEnterMonitor", time_enter_monitor());
+    check!("ActiveModeRate", active_mode_rate());
+    check!("MonitorModeRate", monitor_mode_rate());
+    check!("FreqHoppingEn", freq_hopping_en());
+    check!("RadianValue", radian_value());
+    check!("OffsetLeftRight", offset_left_right());
+    check!("OffsetUpDown", offset_up_down());
+    check!("DistanceLeftRight", distance_left_right());
+    check!("DistanceUpDown", distance_up_down());
+    check!("DistanceZoom", distance_zoom());
+    check!("CipherMid", cipher_mid());
+    check!("CipherLow", cipher_low());
+    check!("LibraryVersion", library_version());
+    check!("ChipId", chip_id());
+    check!("GMode", g_mode());
+    check!("PowerMode", power_mode());
+    check!("FirmwareId", firmware_id());
+    check!("OperatingState", operating_state());
+    check!("FocaltechId", focaltech_id());
+    check!("ErrorCode", error_code());
+    check!("BigAreaThreshold", big_area_threshold());
+    check!("ReleaseCodeId", release_code_id());
+    check!("FaceDecMode", face_dec_mode());
+    check!("State", state());
+
+    // A register added to device.yaml must be added above too.
+    let table: BTreeSet<_> = REGISTERS.iter().map(|r| r.name).collect();
+    assert_eq!(table, checked);
+}
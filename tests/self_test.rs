@@ -0,0 +1,107 @@
+//! `self_test()` against scripted identity registers and channel data.
+
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::delay::NoopDelay;
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use ft6336u_dd::{FT6336U_CHIP_ID, FT6336U_I2C_ADDRESS, Ft6336u, HealthReport, SelfTestLimits};
+
+fn read(register: u8, bytes: &[u8]) -> Transaction {
+    Transaction::write_read(FT6336U_I2C_ADDRESS, vec![register], bytes.to_vec())
+}
+
+fn write(register: u8, value: u8) -> Transaction {
+    Transaction::write(FT6336U_I2C_ADDRESS, vec![register, value])
+}
+
+/// One factory readout of `values` with the given `FactoryDataType`.
+fn readout(data_type: u8, values: &[u16]) -> Vec<Transaction> {
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+    vec![
+        write(0x06, data_type),
+        read(0x00, &[0x40]),
+        write(0x00, 0xC0),
+        read(0x00, &[0x40]),
+        write(0x01, 0),
+        read(0x10, &bytes),
+    ]
+}
+
+/// Run the self-test on a three-channel panel reading `raw`, then `diffs`.
+fn run(limits: &SelfTestLimits, raw: [u16; 3], diffs: &[[i16; 3]]) -> HealthReport {
+    let mut script = vec![
+        read(0xA3, &[FT6336U_CHIP_ID]),
+        read(0xA6, &[5]),
+        read(0xA1, &[0x10, 0x02]),
+        write(0x00, 0x40),
+        read(0x02, &[2]),
+        read(0x03, &[1]),
+    ];
+    script.extend(readout(0, &raw));
+    for diff in diffs {
+        script.extend(readout(1, &diff.map(|d| d as u16)));
+    }
+    script.push(write(0x00, 0x00));
+    let mut mock = Mock::new(&script);
+
+    let mut touch = Ft6336u::new(mock.clone());
+    let report = touch.self_test(limits, &mut NoopDelay).unwrap();
+    drop(touch);
+    mock.done();
+    report
+}
+
+#[test]
+fn flags_open_and_shorted_channels() {
+    let limits = SelfTestLimits {
+        noise_frames: 0,
+        ..SelfTestLimits::default()
+    };
+    let report = run(&limits, [500, 2000, 40000], &[]);
+
+    assert!(report.reachable);
+    assert!(report.chip_id_ok());
+    assert_eq!(report.firmware_id, 5);
+    assert_eq!(report.library_version, 0x1002);
+    assert_eq!(report.channel_count, 3);
+    assert_eq!(report.open_channels, 0b001);
+    assert_eq!(report.short_channels, 0b100);
+    assert_eq!(report.noise, None);
+    assert!(report.noise_ok);
+}
+
+#[test]
+fn reports_the_noisiest_channel_against_the_limit() {
+    let diffs = [[0, 10, -5], [0, 50, 5]];
+    let limits = SelfTestLimits {
+        max_noise: 40,
+        noise_frames: 2,
+        ..SelfTestLimits::default()
+    };
+
+    let report = run(&limits, [2000; 3], &diffs);
+    assert_eq!(report.open_channels | report.short_channels, 0);
+    assert_eq!(report.noise, Some(40));
+    assert_eq!(report.noisiest_channel, Some(1));
+    assert!(report.noise_ok);
+
+    let stricter = SelfTestLimits {
+        max_noise: 39,
+        ..limits
+    };
+    assert!(!run(&stricter, [2000; 3], &diffs).noise_ok);
+}
+
+#[test]
+fn unreachable_controller_is_a_report_not_an_error() {
+    let mut mock = Mock::new(&[read(0xA3, &[0]).with_error(ErrorKind::Other)]);
+
+    let mut touch = Ft6336u::new(mock.clone());
+    let report = touch
+        .self_test(&SelfTestLimits::default(), &mut NoopDelay)
+        .unwrap();
+    assert_eq!(report, HealthReport::default());
+    assert!(!report.reachable);
+
+    drop(touch);
+    mock.done();
+}
@@ -0,0 +1,68 @@
+//! Touch tracking in an `Ft6336uState` attached to a shared bus per call.
+
+use embedded_hal::i2c::I2c;
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use ft6336u_dd::{FT6336U_I2C_ADDRESS, Ft6336uState, TouchStatus};
+
+/// Another device on the same bus.
+const EEPROM: u8 = 0x50;
+
+/// Burst read of 0x01-0x0E with contact 0 at `(x, 100)`, or no contact.
+fn burst(x: Option<u8>) -> Transaction {
+    let mut bytes = vec![0; 14];
+    if let Some(x) = x {
+        bytes[1] = 1;
+        bytes[2] = 0x80;
+        bytes[3] = x;
+        bytes[5] = 100;
+    }
+    Transaction::write_read(FT6336U_I2C_ADDRESS, vec![0x01], bytes)
+}
+
+#[test]
+fn tracking_carries_over_between_attachments() {
+    let eeprom_write = || Transaction::write(EEPROM, vec![0x00, 0xAB]);
+    let mut bus = Mock::new(&[
+        burst(Some(10)),
+        eeprom_write(),
+        burst(Some(20)),
+        eeprom_write(),
+        burst(None),
+    ]);
+    let mut touch = Ft6336uState::new();
+
+    let mut statuses = Vec::new();
+    for round in 0..3 {
+        if round > 0 {
+            // The bus is free between attachments.
+            bus.write(EEPROM, &[0x00, 0xAB]).unwrap();
+        }
+        let data = touch.attach(&mut bus).scan().unwrap();
+        statuses.push((data.points[0].status, data.points[0].x));
+    }
+
+    assert_eq!(
+        statuses,
+        [
+            (TouchStatus::Touch, 10),
+            (TouchStatus::Stream, 20),
+            (TouchStatus::Release, 20),
+        ]
+    );
+    assert_eq!(touch.touch_data().points[0].status, TouchStatus::Release);
+    bus.done();
+}
+
+#[test]
+fn fresh_state_starts_over() {
+    let mut bus = Mock::new(&[burst(Some(10)), burst(Some(10))]);
+    let mut first = Ft6336uState::new();
+    let mut second = Ft6336uState::new();
+
+    let a = first.attach(&mut bus).scan().unwrap();
+    let b = second.attach(&mut bus).scan().unwrap();
+    // Each state tracks its own contact, so both see a touch-down.
+    assert_eq!(a.points[0].status, TouchStatus::Touch);
+    assert_eq!(b.points[0].status, TouchStatus::Touch);
+    bus.done();
+}
@@ -0,0 +1,100 @@
+//! `touch_stream()` over a simulated controller whose transfers each suspend
+//! once.
+
+#![cfg(feature = "futures")]
+
+mod common;
+
+use core::cell::Cell;
+use core::convert::Infallible;
+use core::pin::pin;
+
+use common::{Bus, complete, poll_n};
+use embedded_hal::i2c::ErrorKind;
+use ft6336u_dd::stream::{ScanTrigger, TouchStreamError, touch_stream};
+use ft6336u_dd::{Ft6336uAsync, Ft6336uError, TouchStatus};
+use futures_util::StreamExt;
+
+/// Fires immediately, counting how often it was waited on.
+struct Ticks<'a>(&'a Cell<usize>);
+
+impl ScanTrigger for Ticks<'_> {
+    type Error = Infallible;
+
+    async fn wait(&mut self) -> Result<(), Infallible> {
+        self.0.set(self.0.get() + 1);
+        Ok(())
+    }
+}
+
+/// An INT pin that can't be read.
+struct Broken;
+
+impl ScanTrigger for Broken {
+    type Error = ErrorKind;
+
+    async fn wait(&mut self) -> Result<(), ErrorKind> {
+        Err(ErrorKind::Other)
+    }
+}
+
+#[test]
+fn yields_only_changed_frames() {
+    let a = Some((100, 200));
+    let b = Some((110, 200));
+    let mut touch = Ft6336uAsync::new(Bus::scripted([Ok(a), Ok(a), Ok(a), Ok(b), Ok(None)]));
+    let waits = Cell::new(0);
+    let mut stream = pin!(touch_stream(&mut touch, Ticks(&waits)));
+
+    let mut next = || {
+        let data = complete(stream.next()).unwrap().unwrap();
+        (data.points[0].status, data.points[0].x, waits.get())
+    };
+    assert_eq!(next(), (TouchStatus::Touch, 100, 1));
+    assert_eq!(next(), (TouchStatus::Stream, 100, 2));
+    // The third scan repeats the second and is skipped.
+    assert_eq!(next(), (TouchStatus::Stream, 110, 4));
+    assert_eq!(next().0, TouchStatus::Release);
+}
+
+#[test]
+fn errors_are_yielded_and_the_stream_continues() {
+    let a = Some((100, 200));
+    let mut touch = Ft6336uAsync::new(Bus::scripted([Err(ErrorKind::Other), Ok(a)]));
+    let waits = Cell::new(0);
+    let mut stream = pin!(touch_stream(&mut touch, Ticks(&waits)));
+
+    assert!(matches!(
+        complete(stream.next()),
+        Some(Err(TouchStreamError::Touch(Ft6336uError::I2c(
+            ErrorKind::Other
+        ))))
+    ));
+    let data = complete(stream.next()).unwrap().unwrap();
+    assert_eq!(data.points[0].status, TouchStatus::Touch);
+
+    let mut touch = Ft6336uAsync::new(Bus::new());
+    let mut stream = pin!(touch_stream(&mut touch, Broken));
+    assert!(matches!(
+        complete(stream.next()),
+        Some(Err(TouchStreamError::Trigger(ErrorKind::Other)))
+    ));
+}
+
+#[test]
+fn dropped_next_finishes_the_scan_on_the_next_poll() {
+    let mut touch = Ft6336uAsync::new(Bus::with_contact(Some((100, 200))));
+    let waits = Cell::new(0);
+    {
+        let mut stream = pin!(touch_stream(&mut touch, Ticks(&waits)));
+        // Suspended inside the burst read, e.g. another `select!` branch won.
+        assert!(poll_n(stream.next(), 1).is_none());
+        assert_eq!(waits.get(), 1);
+
+        let data = complete(stream.next()).unwrap().unwrap();
+        assert_eq!(data.points[0].status, TouchStatus::Touch);
+        // The same scan was resumed, not started over.
+        assert_eq!(waits.get(), 1);
+    }
+    assert_eq!(touch.release().transfers, 1);
+}
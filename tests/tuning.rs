@@ -0,0 +1,151 @@
+//! `ThresholdTuner` decisions and how `scan_with_tuner()` applies them.
+
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use ft6336u_dd::TouchStatus::{Release, Stream, Touch};
+use ft6336u_dd::{
+    FT6336U_I2C_ADDRESS, Ft6336uState, Quirks, ThresholdTuner, TouchData, TouchPoint, TouchStatus,
+    TunerConfig, TuningPreset,
+};
+
+fn frame(status: TouchStatus, weight: u8) -> TouchData {
+    let mut data = TouchData::default();
+    data.points[0] = TouchPoint {
+        status,
+        weight,
+        ..Default::default()
+    };
+    data.touch_count = u8::from(status != Release);
+    data
+}
+
+/// Normal preset bounds and step, deciding after every contact frame.
+fn tuner(threshold: u8) -> ThresholdTuner {
+    let config = TunerConfig {
+        window_frames: 1,
+        ..TunerConfig::default()
+    };
+    ThresholdTuner::new(config, threshold)
+}
+
+#[test]
+fn small_differences_are_left_alone() {
+    let mut tuner = tuner(30);
+    // Target 33 is within the hysteresis of 4.
+    assert_eq!(tuner.observe(&frame(Touch, 66)), None);
+    // Target 35 is not, and the threshold moves by at most one step.
+    assert_eq!(tuner.observe(&frame(Stream, 70)), Some(34));
+    assert_eq!(tuner.observe(&frame(Stream, 70)), None);
+    assert_eq!(tuner.threshold(), 34);
+}
+
+#[test]
+fn threshold_stays_within_bounds() {
+    let config = TunerConfig::default();
+    assert_eq!(ThresholdTuner::new(config, 200).threshold(), 60);
+    assert_eq!(ThresholdTuner::new(config, 0).threshold(), 15);
+
+    let eager = TunerConfig {
+        hysteresis: 0,
+        window_frames: 1,
+        ..config
+    };
+    let mut high = ThresholdTuner::new(eager, 58);
+    assert_eq!(high.observe(&frame(Touch, 255)), Some(60));
+    assert_eq!(high.observe(&frame(Stream, 255)), None);
+
+    let mut low = ThresholdTuner::new(eager, 17);
+    assert_eq!(low.observe(&frame(Touch, 10)), Some(15));
+    assert_eq!(low.observe(&frame(Stream, 10)), None);
+
+    let swapped = TunerConfig {
+        min_threshold: 60,
+        max_threshold: 15,
+        ..config
+    };
+    assert_eq!(ThresholdTuner::new(swapped, 200).threshold(), 60);
+}
+
+#[test]
+fn false_touches_raise_the_threshold() {
+    let config = TunerConfig {
+        max_false_touches: 1,
+        window_frames: 3,
+        ..TunerConfig::default()
+    };
+    // Weak contacts alone would lower the threshold.
+    let blips = [
+        frame(Touch, 20),
+        frame(Release, 0),
+        frame(Touch, 20),
+        frame(Release, 0),
+        frame(Touch, 20),
+    ];
+    let decide = |threshold| {
+        let mut tuner = ThresholdTuner::new(config, threshold);
+        blips.iter().map(|f| tuner.observe(f)).last().flatten()
+    };
+    assert_eq!(decide(30), Some(34));
+    // Already at the maximum, so there is nothing to write.
+    assert_eq!(decide(60), None);
+}
+
+#[test]
+fn zero_step_never_reports_a_change() {
+    let config = TunerConfig {
+        step: 0,
+        window_frames: 1,
+        ..TunerConfig::default()
+    };
+    let mut tuner = ThresholdTuner::new(config, 30);
+    assert_eq!(tuner.observe(&frame(Touch, 120)), None);
+    assert_eq!(tuner.threshold(), 30);
+}
+
+#[test]
+fn presets() {
+    assert_eq!(
+        TunerConfig::default(),
+        TunerConfig::preset(TuningPreset::Normal)
+    );
+    let [normal, glove, wet] = [
+        TuningPreset::Normal,
+        TuningPreset::Glove,
+        TuningPreset::WetFinger,
+    ]
+    .map(TunerConfig::preset);
+    for config in [normal, glove, wet] {
+        assert_eq!(config.normalized(), config);
+        assert!(config.step > 0 && config.window_frames > 0);
+    }
+    // Gloves need lower thresholds, wet fingers higher ones and less
+    // tolerance for false touches.
+    assert!(glove.min_threshold < normal.min_threshold);
+    assert!(glove.weight_ratio_percent < normal.weight_ratio_percent);
+    assert!(wet.min_threshold > normal.min_threshold);
+    assert!(wet.max_false_touches < normal.max_false_touches);
+}
+
+#[test]
+fn scan_skips_the_write_when_threshold_is_ignored() {
+    let mut bytes = vec![0; 14];
+    bytes[1] = 1;
+    bytes[2] = 0x80;
+    bytes[6] = 100; // weight
+    let mut mock = Mock::new(&[Transaction::write_read(
+        FT6336U_I2C_ADDRESS,
+        vec![0x01],
+        bytes,
+    )]);
+    let mut state = Ft6336uState::new();
+    state.set_quirks(Quirks {
+        unsupported_registers: &[0x80],
+        ..Quirks::NONE
+    });
+    let mut tuner = tuner(15);
+
+    let data = state.attach(&mut mock).scan_with_tuner(&mut tuner).unwrap();
+    assert_eq!(data.points[0].status, Touch);
+    // The tuner still moved; only the write was left out.
+    assert_eq!(tuner.threshold(), 19);
+    mock.done();
+}
@@ -0,0 +1,217 @@
+//! `upgrade_firmware()` against a simulated FocalTech bootloader.
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
+use ft6336u_dd::firmware::checksum;
+use ft6336u_dd::{
+    BootloaderConfig, FT6336U_I2C_ADDRESS, Ft6336u, Ft6336uError, UpgradeError, UpgradeProgress,
+};
+
+struct NoDelay;
+
+impl DelayNs for NoDelay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+/// Speaks the bootloader protocol: 0xAA/0x55 to the `State` register (0xBC)
+/// resets into the bootloader, then 0x55 0xAA, 0x90 (read ID), 0x61 (erase),
+/// 0xBF (write packet), 0xCC (checksum) and 0x07 (reset).
+#[derive(Default)]
+struct Bootloader {
+    boot_id: [u8; 2],
+    /// Report a wrong checksum, as after a failed flash write.
+    corrupt: bool,
+    reset_sequence: Vec<u8>,
+    in_bootloader: bool,
+    unlocked: bool,
+    erased: bool,
+    flash: Vec<u8>,
+    /// Address of every 0xBF packet, in order.
+    packets: Vec<usize>,
+    resets: usize,
+}
+
+impl Bootloader {
+    fn new() -> Self {
+        Self {
+            boot_id: BootloaderConfig::default().boot_id,
+            ..Self::default()
+        }
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        match data {
+            [0xBC, value] => {
+                self.reset_sequence.push(*value);
+                if self.reset_sequence.ends_with(&[0xAA, 0x55]) {
+                    self.in_bootloader = true;
+                }
+            }
+            [0x55, 0xAA] => {
+                assert!(self.in_bootloader, "upgrade command outside the bootloader");
+                self.unlocked = true;
+            }
+            [0x61] => {
+                assert!(self.unlocked, "erase before the upgrade command");
+                self.erased = true;
+                self.flash.clear();
+            }
+            [0xBF, a2, a1, a0, l1, l0, payload @ ..] => {
+                assert!(self.erased, "write before erase");
+                let address = usize::from(*a2) << 16 | usize::from(*a1) << 8 | usize::from(*a0);
+                let len = usize::from(*l1) << 8 | usize::from(*l0);
+                assert_eq!(payload.len(), len, "packet length header");
+                assert_eq!(address, self.flash.len(), "packets must be contiguous");
+                self.flash.extend_from_slice(payload);
+                self.packets.push(address);
+            }
+            [0x07] => {
+                self.resets += 1;
+                self.in_bootloader = false;
+                self.unlocked = false;
+            }
+            _ => panic!("unexpected write {data:02x?}"),
+        }
+    }
+
+    fn write_read(&mut self, command: &[u8], response: &mut [u8]) {
+        assert!(
+            self.unlocked,
+            "command {command:02x?} outside the bootloader"
+        );
+        match command {
+            [0x90, 0x00, 0x00, 0x00] => response.copy_from_slice(&self.boot_id),
+            [0xCC] => response[0] = checksum(&self.flash) ^ u8::from(self.corrupt),
+            _ => panic!("unexpected command {command:02x?}"),
+        }
+    }
+}
+
+impl ErrorType for Bootloader {
+    type Error = ErrorKind;
+}
+
+impl I2c for Bootloader {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        assert_eq!(address, FT6336U_I2C_ADDRESS);
+        match operations {
+            [Operation::Write(data)] => self.write(data),
+            [Operation::Write(command), Operation::Read(response)] => {
+                self.write_read(command, response)
+            }
+            _ => panic!("unexpected transaction shape"),
+        }
+        Ok(())
+    }
+}
+
+fn upgrade(
+    bootloader: Bootloader,
+    image: &[u8],
+) -> (
+    Result<(), Ft6336uError<ErrorKind>>,
+    Bootloader,
+    Vec<UpgradeProgress>,
+) {
+    let mut touch = Ft6336u::new(bootloader);
+    let mut progress = Vec::new();
+    let result = touch.upgrade_firmware(image, &BootloaderConfig::default(), &mut NoDelay, |p| {
+        progress.push(p)
+    });
+    (result, touch.release(), progress)
+}
+
+fn image(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 + 3) as u8).collect()
+}
+
+#[test]
+fn flashes_image_in_packets() {
+    let image = image(300);
+    let (result, bootloader, progress) = upgrade(Bootloader::new(), &image);
+    result.unwrap();
+
+    assert_eq!(bootloader.packets, [0, 128, 256]);
+    assert_eq!(bootloader.flash, image);
+    assert_eq!(bootloader.resets, 1);
+    assert!(!bootloader.in_bootloader);
+    assert_eq!(
+        progress,
+        [
+            UpgradeProgress::EnteringBootloader,
+            UpgradeProgress::Erasing,
+            UpgradeProgress::Writing {
+                written: 0,
+                total: 300
+            },
+            UpgradeProgress::Writing {
+                written: 128,
+                total: 300
+            },
+            UpgradeProgress::Writing {
+                written: 256,
+                total: 300
+            },
+            UpgradeProgress::Writing {
+                written: 300,
+                total: 300
+            },
+            UpgradeProgress::Verifying,
+            UpgradeProgress::Resetting,
+        ]
+    );
+}
+
+#[test]
+fn checksum_mismatch_stays_in_bootloader() {
+    let image = image(200);
+    let bootloader = Bootloader {
+        corrupt: true,
+        ..Bootloader::new()
+    };
+    let (result, bootloader, _) = upgrade(bootloader, &image);
+
+    let expected = checksum(&image);
+    assert!(matches!(
+        result,
+        Err(Ft6336uError::Upgrade(UpgradeError::ChecksumMismatch { expected: e, found }))
+            if e == expected && found == expected ^ 1
+    ));
+    assert_eq!(bootloader.resets, 0);
+    assert!(bootloader.in_bootloader);
+}
+
+#[test]
+fn boot_id_mismatch_resets_without_erasing() {
+    let bootloader = Bootloader {
+        boot_id: [0x12, 0x34],
+        ..Bootloader::new()
+    };
+    let (result, bootloader, _) = upgrade(bootloader, &image(10));
+
+    assert!(matches!(
+        result,
+        Err(Ft6336uError::Upgrade(UpgradeError::BootIdMismatch {
+            expected: [0x79, 0x18],
+            found: [0x12, 0x34],
+        }))
+    ));
+    assert!(!bootloader.erased);
+    assert!(bootloader.packets.is_empty());
+    assert_eq!(bootloader.resets, 1);
+}
+
+#[test]
+fn rejects_empty_image_without_touching_the_bus() {
+    let (result, bootloader, progress) = upgrade(Bootloader::new(), &[]);
+    assert!(matches!(
+        result,
+        Err(Ft6336uError::Upgrade(UpgradeError::EmptyImage))
+    ));
+    assert!(bootloader.reset_sequence.is_empty());
+    assert!(progress.is_empty());
+}